@@ -1,5 +1,6 @@
 use embedded_graphics::Drawing;
 use emui::widgets;
+use std::iter;
 use std::str;
 use std::thread;
 use std::time;
@@ -21,19 +22,45 @@ enum Mode {
     Monochrome,
 }
 
+#[derive(Clone, Debug)]
+enum Message {
+    Ok,
+    Pause(bool),
+    FreezeStats(bool),
+}
+
+#[derive(Debug)]
+struct Window<D> {
+    display: D,
+    size: embedded_graphics::geometry::Size,
+}
+
+#[derive(Debug)]
+struct WindowClosed;
+
+#[derive(Debug)]
+struct SystemClock {
+    start: time::Instant,
+}
+
 #[derive(Debug, Default)]
 struct App {
     data_processing: DataProcessing,
     system_stats: SystemStats,
+    paused: bool,
+    stats_frozen: bool,
 
     main_container: widgets::container::State,
     frame: widgets::frame::State,
+    settings_container: widgets::container::State,
+    pause_checkbox: widgets::checkbox::State,
+    freeze_toggle: widgets::toggle::State,
     ok_button: widgets::button::State,
 }
 
 #[derive(Debug, Default)]
 struct DataProcessing {
-    start: Option<time::Instant>,
+    start: Option<time::Duration>,
     upload_ratio: f32,
     transcode_ratio: f32,
 
@@ -58,12 +85,24 @@ struct SystemStats {
 }
 
 impl App {
-    fn update(&mut self) {
-        self.data_processing.update();
-        self.system_stats.update();
+    fn advance(&mut self, now: time::Duration) {
+        if !self.paused {
+            self.data_processing.update(now);
+        }
+        if !self.stats_frozen {
+            self.system_stats.update();
+        }
+    }
+
+    fn handle(&mut self, message: Message) {
+        match message {
+            Message::Ok => self.data_processing.restart(),
+            Message::Pause(paused) => self.paused = paused,
+            Message::FreezeStats(frozen) => self.stats_frozen = frozen,
+        }
     }
 
-    fn view<'a, T>(&'a mut self) -> impl emui::widget::Node<'a, T>
+    fn view<'a, T>(&'a mut self) -> impl emui::widget::Node<'a, T, Message>
     where
         T: emui::theme::Theme<'a>,
     {
@@ -76,22 +115,43 @@ impl App {
                     self.data_processing.view(),
                 ),
                 self.system_stats.view(),
-                widgets::button::Button::new(&mut self.ok_button, "OK"),
+                widgets::container::Row::new(
+                    &mut self.settings_container,
+                    (
+                        widgets::checkbox::Checkbox::new(
+                            &mut self.pause_checkbox,
+                            "Pause",
+                            self.paused,
+                        )
+                        .on_toggle(Message::Pause),
+                        widgets::toggle::Toggle::new(
+                            &mut self.freeze_toggle,
+                            "Freeze stats",
+                            self.stats_frozen,
+                        )
+                        .on_toggle(Message::FreezeStats),
+                    ),
+                ),
+                widgets::button::Button::new(&mut self.ok_button, "OK").on_press(Message::Ok),
             ),
         )
     }
 }
 
 impl DataProcessing {
-    fn update(&mut self) {
-        let start = self.start.get_or_insert_with(time::Instant::now);
-        let millis = start.elapsed().as_millis();
+    fn update(&mut self, now: time::Duration) {
+        let start = *self.start.get_or_insert(now);
+        let millis = (now - start).as_millis();
 
         self.upload_ratio = (millis as f32 / 4300.0) % 1.0;
         self.transcode_ratio = (millis as f32 / 9500.0) % 1.0;
     }
 
-    fn view<'a, T>(&'a mut self) -> impl emui::widget::Node<'a, T>
+    fn restart(&mut self) {
+        self.start = None;
+    }
+
+    fn view<'a, T, M>(&'a mut self) -> impl emui::widget::Node<'a, T, M>
     where
         T: emui::theme::Theme<'a>,
     {
@@ -132,7 +192,7 @@ impl SystemStats {
             self.system.get_used_memory() as f32 / self.system.get_total_memory() as f32;
     }
 
-    fn view<'a, T>(&'a mut self) -> impl emui::widget::Node<'a, T>
+    fn view<'a, T, M>(&'a mut self) -> impl emui::widget::Node<'a, T, M>
     where
         T: emui::theme::Theme<'a>,
     {
@@ -156,87 +216,175 @@ impl SystemStats {
     }
 }
 
-fn main() {
-    use structopt::StructOpt;
-    let options = Options::from_args();
+impl emui::app::Application<FullColor> for App {
+    type Message = Message;
 
-    use embedded_graphics::pixelcolor::RgbColor;
+    fn update(&mut self, message: Self::Message) {
+        self.handle(message);
+    }
 
-    match options.mode {
-        Mode::Rgb => {
-            let theme = emui::theme::SimpleTheme::<FullColor, FullFont<'_>> {
-                spacing: 2.0,
-                background_color: embedded_graphics::pixelcolor::Rgb888::BLACK,
-                text_color: embedded_graphics::pixelcolor::Rgb888::WHITE,
-                border_color: embedded_graphics::pixelcolor::Rgb888::RED,
-                fill_color: embedded_graphics::pixelcolor::Rgb888::BLUE,
-                phantom: core::marker::PhantomData,
-            };
+    fn tick(&mut self, now: time::Duration) {
+        self.advance(now);
+    }
 
-            let display = embedded_graphics_simulator::DisplayBuilder::new()
-                .size(320, 240)
-                .scale(2)
-                .build_rgb();
+    fn view<V>(&mut self, viewer: V) -> V::Output
+    where
+        V: emui::app::Viewer<FullColor, Self::Message>,
+    {
+        use embedded_graphics::pixelcolor::RgbColor;
+
+        let theme = emui::theme::SimpleTheme::<FullColor, FullFont<'_>> {
+            spacing: 2.0,
+            background_color: embedded_graphics::pixelcolor::Rgb888::BLACK,
+            text_color: embedded_graphics::pixelcolor::Rgb888::WHITE,
+            border_color: embedded_graphics::pixelcolor::Rgb888::RED,
+            fill_color: embedded_graphics::pixelcolor::Rgb888::BLUE,
+            stripe_color: embedded_graphics::pixelcolor::Rgb888::new(0x20, 0x20, 0x20),
+            focus_color: embedded_graphics::pixelcolor::Rgb888::GREEN,
+            hover_color: embedded_graphics::pixelcolor::Rgb888::new(0x40, 0x40, 0x40),
+            pressed_color: embedded_graphics::pixelcolor::Rgb888::new(0x80, 0x00, 0x00),
+            disabled_color: embedded_graphics::pixelcolor::Rgb888::new(0x80, 0x80, 0x80),
+            phantom: core::marker::PhantomData,
+        };
+
+        viewer.view(&theme, &mut App::view(self))
+    }
+}
 
-            let mut app = App::default();
+impl emui::app::Application<MonochromeColor> for App {
+    type Message = Message;
 
-            let mut emui = emui::Emui::new(display);
+    fn update(&mut self, message: Self::Message) {
+        self.handle(message);
+    }
 
-            loop {
-                app.update();
+    fn tick(&mut self, now: time::Duration) {
+        self.advance(now);
+    }
 
-                emui.drawing_mut().draw(embedded_graphics::egrectangle!(
-                    (0, 0),
-                    (320, 240),
-                    fill = Some(embedded_graphics::pixelcolor::Rgb888::BLACK)
-                ));
+    fn view<V>(&mut self, viewer: V) -> V::Output
+    where
+        V: emui::app::Viewer<MonochromeColor, Self::Message>,
+    {
+        let theme = emui::theme::SimpleTheme::<MonochromeColor, MonochromeFont<'_>> {
+            spacing: 2.0,
+            background_color: embedded_graphics::pixelcolor::BinaryColor::Off,
+            text_color: embedded_graphics::pixelcolor::BinaryColor::On,
+            border_color: embedded_graphics::pixelcolor::BinaryColor::On,
+            fill_color: embedded_graphics::pixelcolor::BinaryColor::On,
+            stripe_color: embedded_graphics::pixelcolor::BinaryColor::Off,
+            focus_color: embedded_graphics::pixelcolor::BinaryColor::On,
+            hover_color: embedded_graphics::pixelcolor::BinaryColor::Off,
+            pressed_color: embedded_graphics::pixelcolor::BinaryColor::On,
+            disabled_color: embedded_graphics::pixelcolor::BinaryColor::On,
+            phantom: core::marker::PhantomData,
+        };
+
+        viewer.view(&theme, &mut App::view(self))
+    }
+}
 
-                emui.update(320, 240, &theme, &mut app.view());
+impl Drawing<FullColor> for Window<embedded_graphics_simulator::RgbDisplay> {
+    fn draw<T>(&mut self, item: T)
+    where
+        T: IntoIterator<Item = embedded_graphics::drawable::Pixel<FullColor>>,
+    {
+        self.display.draw(item)
+    }
+}
 
-                if emui.drawing_mut().run_once() {
-                    break;
-                }
+impl emui::app::Display<FullColor> for Window<embedded_graphics_simulator::RgbDisplay> {
+    type Error = WindowClosed;
 
-                thread::sleep(time::Duration::from_millis(100));
-            }
+    fn size(&self) -> embedded_graphics::geometry::Size {
+        self.size
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.display.run_once() {
+            Err(WindowClosed)
+        } else {
+            Ok(())
         }
-        Mode::Monochrome => {
-            let theme = emui::theme::SimpleTheme::<MonochromeColor, MonochromeFont<'_>> {
-                spacing: 2.0,
-                background_color: embedded_graphics::pixelcolor::BinaryColor::Off,
-                text_color: embedded_graphics::pixelcolor::BinaryColor::On,
-                border_color: embedded_graphics::pixelcolor::BinaryColor::On,
-                fill_color: embedded_graphics::pixelcolor::BinaryColor::On,
-                phantom: core::marker::PhantomData,
-            };
+    }
+}
 
-            let display = embedded_graphics_simulator::DisplayBuilder::new()
-                .size(160, 120)
-                .scale(2)
-                .theme(embedded_graphics_simulator::BinaryColorTheme::OledBlue)
-                .build_binary();
+impl Drawing<MonochromeColor> for Window<embedded_graphics_simulator::BinaryDisplay> {
+    fn draw<T>(&mut self, item: T)
+    where
+        T: IntoIterator<Item = embedded_graphics::drawable::Pixel<MonochromeColor>>,
+    {
+        self.display.draw(item)
+    }
+}
+
+impl emui::app::Display<MonochromeColor> for Window<embedded_graphics_simulator::BinaryDisplay> {
+    type Error = WindowClosed;
+
+    fn size(&self) -> embedded_graphics::geometry::Size {
+        self.size
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.display.run_once() {
+            Err(WindowClosed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl SystemClock {
+    fn new() -> Self {
+        let start = time::Instant::now();
+        Self { start }
+    }
+}
 
-            let mut app = App::default();
+impl emui::app::Clock for SystemClock {
+    fn now(&mut self) -> time::Duration {
+        self.start.elapsed()
+    }
 
-            let mut emui = emui::Emui::new(display);
+    fn delay(&mut self, duration: time::Duration) {
+        thread::sleep(duration);
+    }
+}
 
-            loop {
-                app.update();
+fn run<C, D>(display: D)
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+    D: emui::app::Display<C>,
+    App: emui::app::Application<C>,
+{
+    let mut app = App::default();
+    let mut runner = emui::app::Runner::new(display, iter::empty(), SystemClock::new());
+    runner.run(&mut app);
+}
 
-                emui.drawing_mut().draw(embedded_graphics::egrectangle!(
-                    (0, 0),
-                    (160, 120),
-                    fill = Some(embedded_graphics::pixelcolor::BinaryColor::Off)
-                ));
+fn main() {
+    use structopt::StructOpt;
+    let options = Options::from_args();
 
-                emui.update(160, 120, &theme, &mut app.view());
+    match options.mode {
+        Mode::Rgb => {
+            let display = embedded_graphics_simulator::DisplayBuilder::new()
+                .size(320, 240)
+                .scale(2)
+                .build_rgb();
+            let size = embedded_graphics::geometry::Size::new(320, 240);
 
-                if emui.drawing_mut().run_once() {
-                    break;
-                }
+            run(Window { display, size });
+        }
+        Mode::Monochrome => {
+            let display = embedded_graphics_simulator::DisplayBuilder::new()
+                .size(160, 120)
+                .scale(2)
+                .theme(embedded_graphics_simulator::BinaryColorTheme::OledBlue)
+                .build_binary();
+            let size = embedded_graphics::geometry::Size::new(160, 120);
 
-                thread::sleep(time::Duration::from_millis(100));
-            }
+            run(Window { display, size });
         }
     }
 }
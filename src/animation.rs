@@ -0,0 +1,100 @@
+//! Per-frame animation of the widget tree.
+//!
+//! Once per frame, after the layout pass, every widget that has been laid out gets to advance its
+//! animations to the time of the frame via `widget::Widget::animate`, e.g. to keep a list scrolling
+//! after a fling.  Widgets keep any animation state in their own `State`, so they can pick up where
+//! they left off in the next frame.
+use crate::layout;
+use crate::theme;
+use crate::widget;
+use core::time;
+
+/// Calls `widget::Widget::animate` on every widget in the tree that has been laid out, while
+/// `focus` has the focus.
+pub fn animate<'a, T, M, N>(
+    layout: &layout::Layout,
+    widget: &mut N,
+    now: time::Duration,
+    focus: Option<widget::Id>,
+) where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    widget.visit_mut(AnimationVisitor { layout, now, focus });
+}
+
+struct AnimationVisitor<'a> {
+    layout: &'a layout::Layout,
+    now: time::Duration,
+    focus: Option<widget::Id>,
+}
+
+struct AnimationChildVisitor<'a> {
+    layout: &'a layout::Layout,
+    now: time::Duration,
+    focus: Option<widget::Id>,
+}
+
+impl<'a> AnimationVisitor<'a> {
+    fn animate<'b, T, M, N>(&self, node: &mut N)
+    where
+        T: theme::Theme<'b>,
+        N: widget::Widget<'b, T, M>,
+    {
+        if let Some(geometry) = self.layout.get_geometry(node.layout_state()) {
+            let context = widget::AnimationContext {
+                size: geometry.embedded_size(),
+                now: self.now,
+                focus: self.focus,
+                layout: self.layout,
+            };
+            node.animate(&context);
+        }
+    }
+}
+
+impl<'a, 'b, T, M> widget::MutVisitor<'b, T, M> for AnimationVisitor<'a>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = ();
+    type MutChildVisitor = AnimationChildVisitor<'a>;
+
+    fn accept_leaf_mut<N>(self, node: &mut N) -> Self::Output
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        self.animate(node);
+    }
+
+    fn accept_node_mut<N>(self, node: &mut N, _child_count: usize) -> Self::MutChildVisitor
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        self.animate(node);
+        let layout = self.layout;
+        let now = self.now;
+        let focus = self.focus;
+
+        AnimationChildVisitor { layout, now, focus }
+    }
+}
+
+impl<'a, 'b, T, M> widget::MutChildVisitor<'b, T, M> for AnimationChildVisitor<'a>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = ();
+
+    fn accept_child_mut<W>(&mut self, widget: &mut W)
+    where
+        W: widget::Node<'b, T, M>,
+    {
+        let layout = self.layout;
+        let now = self.now;
+        let focus = self.focus;
+        widget.visit_mut(AnimationVisitor { layout, now, focus });
+    }
+
+    fn end(self) -> Self::Output {}
+}
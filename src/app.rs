@@ -0,0 +1,263 @@
+//! An application runtime that drives the update/view loop.
+//!
+//! Applications implement `Application`, and a `Runner` takes care of feeding input events to the
+//! widget tree, passing the resulting messages back to the application, and drawing frames at a
+//! steady rate.  Gestures are recognized from the pointer events using the clock.  The runner is
+//! parametrised over the display, the input source and the clock, so the same application can run
+//! both on a simulator and on hardware.
+use crate::event;
+use crate::gesture;
+use crate::input;
+use crate::message;
+use crate::theme;
+use crate::widget;
+use crate::Emui;
+use core::time;
+
+pub trait Application<C> {
+    type Message;
+
+    /// Updates the application state in response to a message emitted by a widget.
+    fn update(&mut self, message: Self::Message);
+
+    /// Called once per frame with the current time, before the frame is drawn.
+    fn tick(&mut self, _now: time::Duration) {}
+
+    /// Builds the widget tree for the current state and passes it to `viewer` along with a theme.
+    fn view<V>(&mut self, viewer: V) -> V::Output
+    where
+        V: Viewer<C, Self::Message>;
+}
+
+/// Something that can do work with a widget tree, e.g. render it or handle an event with it.
+///
+/// This exists so that the theme and the widget tree can be created by the application, where
+/// their concrete types (and lifetimes) are known.
+pub trait Viewer<C, M> {
+    type Output;
+
+    fn view<'a, T, W>(self, theme: &T, widget: &mut W) -> Self::Output
+    where
+        T: theme::Theme<'a, Color = C>,
+        T::Font: embedded_graphics::fonts::Font<'a, C>,
+        W: widget::Node<'a, T, M>;
+}
+
+/// A display that frames can be drawn to.
+pub trait Display<C>: embedded_graphics::Drawing<C>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+{
+    type Error;
+
+    /// The size of the display, in pixels.
+    fn size(&self) -> embedded_graphics::geometry::Size;
+
+    /// Shows everything that has been drawn since the last flush.
+    fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+/// A monotonic source of time.
+pub trait Clock {
+    /// The time elapsed since some fixed point in the past, e.g. since the device booted.
+    fn now(&mut self) -> time::Duration;
+
+    /// Blocks for the specified duration.
+    fn delay(&mut self, duration: time::Duration);
+}
+
+pub struct Runner<C, D, I, K>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+    D: Display<C>,
+{
+    emui: Emui<C, D>,
+    input: I,
+    clock: K,
+    gestures: gesture::Recognizer,
+    frame_duration: time::Duration,
+}
+
+struct EventViewer<'a, C, D>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+    D: embedded_graphics::Drawing<C>,
+{
+    emui: &'a mut Emui<C, D>,
+    event: event::Event,
+    now: time::Duration,
+}
+
+struct RenderViewer<'a, C, D>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+    D: embedded_graphics::Drawing<C>,
+{
+    emui: &'a mut Emui<C, D>,
+    size: embedded_graphics::geometry::Size,
+    now: time::Duration,
+}
+
+impl<C, D, I, K> Runner<C, D, I, K>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+    D: Display<C>,
+    I: input::Source,
+    K: Clock,
+{
+    pub fn new(display: D, input: I, clock: K) -> Self {
+        let emui = Emui::new(display);
+        let gestures = gesture::Recognizer::default();
+        let frame_duration = time::Duration::from_millis(100);
+        Self {
+            emui,
+            input,
+            clock,
+            gestures,
+            frame_duration,
+        }
+    }
+
+    /// Sets the thresholds used to recognize gestures.
+    pub fn gesture_config(mut self, config: gesture::Config) -> Self {
+        self.gestures = gesture::Recognizer::new(config);
+        self
+    }
+
+    /// Sets the minimum time between frames; defaults to 100 ms.
+    pub fn frame_duration(mut self, frame_duration: time::Duration) -> Self {
+        self.frame_duration = frame_duration;
+        self
+    }
+
+    /// Runs the application until the display fails, returning the error.
+    pub fn run<A>(&mut self, app: &mut A) -> D::Error
+    where
+        A: Application<C>,
+    {
+        loop {
+            if let Err(error) = self.step(app) {
+                return error;
+            }
+        }
+    }
+
+    /// Handles all pending input, then draws and shows a single frame.
+    pub fn step<A>(&mut self, app: &mut A) -> Result<(), D::Error>
+    where
+        A: Application<C>,
+    {
+        let start = self.clock.now();
+
+        loop {
+            let now = self.clock.now();
+            let event = match self.input.poll(now) {
+                Some(event) => event,
+                None => break,
+            };
+
+            self.dispatch(app, event, now);
+
+            if let event::Event::Pointer(pointer) = event {
+                if let Some(gesture) = self.gestures.update(pointer, now) {
+                    self.dispatch(app, gesture.into(), now);
+                }
+            }
+        }
+
+        if let Some(gesture) = self.gestures.poll(start) {
+            self.dispatch(app, gesture.into(), start);
+        }
+
+        app.tick(start);
+
+        let emui = &mut self.emui;
+        let size = emui.drawing().size();
+        let now = start;
+        app.view(RenderViewer { emui, size, now });
+        self.emui.drawing_mut().flush()?;
+
+        let elapsed = self.clock.now().checked_sub(start).unwrap_or_default();
+        if let Some(remaining) = self.frame_duration.checked_sub(elapsed) {
+            self.clock.delay(remaining);
+        }
+
+        Ok(())
+    }
+
+    fn dispatch<A>(&mut self, app: &mut A, event: event::Event, now: time::Duration)
+    where
+        A: Application<C>,
+    {
+        let emui = &mut self.emui;
+        for message in app.view(EventViewer { emui, event, now }) {
+            app.update(message);
+        }
+    }
+
+    pub fn emui(&self) -> &Emui<C, D> {
+        &self.emui
+    }
+
+    pub fn emui_mut(&mut self) -> &mut Emui<C, D> {
+        &mut self.emui
+    }
+
+    pub fn into_emui(self) -> Emui<C, D> {
+        self.emui
+    }
+
+    pub fn input(&self) -> &I {
+        &self.input
+    }
+
+    /// The input source, e.g. for applying a new calibration matrix between frames.
+    pub fn input_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+}
+
+impl<'a, C, D, M> Viewer<C, M> for EventViewer<'a, C, D>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+    D: embedded_graphics::Drawing<C>,
+{
+    type Output = message::Queue<M>;
+
+    fn view<'b, T, W>(self, theme: &T, widget: &mut W) -> Self::Output
+    where
+        T: theme::Theme<'b, Color = C>,
+        T::Font: embedded_graphics::fonts::Font<'b, C>,
+        W: widget::Node<'b, T, M>,
+    {
+        self.emui.handle_event(theme, widget, self.event, self.now)
+    }
+}
+
+impl<'a, C, D, M> Viewer<C, M> for RenderViewer<'a, C, D>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+    D: embedded_graphics::Drawing<C>,
+{
+    type Output = ();
+
+    fn view<'b, T, W>(self, theme: &T, widget: &mut W) -> Self::Output
+    where
+        T: theme::Theme<'b, Color = C>,
+        T::Font: embedded_graphics::fonts::Font<'b, C>,
+        W: widget::Node<'b, T, M>,
+    {
+        let width = self.size.width;
+        let height = self.size.height;
+        let x2 = width as i32 - 1;
+        let y2 = height as i32 - 1;
+
+        let drawing = self.emui.drawing_mut();
+        drawing.draw(embedded_graphics::egrectangle!(
+            (0, 0),
+            (x2, y2),
+            fill = Some(theme.background_color())
+        ));
+        self.emui.update(width, height, theme, widget, self.now);
+    }
+}
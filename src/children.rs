@@ -1,7 +1,7 @@
 use crate::theme;
 use crate::widget;
 
-pub trait Children<'a, T>
+pub trait Children<'a, T, M>
 where
     T: theme::Theme<'a>,
 {
@@ -13,19 +13,19 @@ where
 
     fn visit_children<V>(&self, visitor: V) -> V::Output
     where
-        V: widget::ChildVisitor<'a, T>;
+        V: widget::ChildVisitor<'a, T, M>;
 
     fn visit_children_mut<V>(&mut self, visitor: V) -> V::Output
     where
-        V: widget::MutChildVisitor<'a, T>;
+        V: widget::MutChildVisitor<'a, T, M>;
 }
 
 macro_rules! tuple_nodes {
     ($($W:ident),*) => {
-        impl<'a, T, $($W),*> Children<'a, T> for ($($W,)*)
+        impl<'a, T, M, $($W),*> Children<'a, T, M> for ($($W,)*)
         where
             T: theme::Theme<'a>,
-            $($W: widget::Node<'a, T>,)*
+            $($W: widget::Node<'a, T, M>,)*
         {
             fn len(&self) -> usize {
                 #[allow(dead_code)]
@@ -34,7 +34,7 @@ macro_rules! tuple_nodes {
             }
 
             #[allow(non_snake_case)]
-            fn visit_children<V>(&self, mut visitor: V) -> V::Output where V: widget::ChildVisitor<'a, T> {
+            fn visit_children<V>(&self, mut visitor: V) -> V::Output where V: widget::ChildVisitor<'a, T, M> {
                 match *self {
                     ($(ref $W,)*) => {
                         $(visitor.accept_child($W);)*
@@ -43,7 +43,7 @@ macro_rules! tuple_nodes {
                 }
             }
             #[allow(non_snake_case)]
-            fn visit_children_mut<V>(&mut self, mut visitor: V) -> V::Output where V: widget::MutChildVisitor<'a, T> {
+            fn visit_children_mut<V>(&mut self, mut visitor: V) -> V::Output where V: widget::MutChildVisitor<'a, T, M> {
                 match *self {
                     ($(ref mut $W,)*) => {
                         $(visitor.accept_child_mut($W);)*
@@ -0,0 +1,175 @@
+//! Input events that can be fed into the user interface.
+
+/// The capacity of an event queue, i.e. how many events can be posted while handling an event.
+pub type Capacity = heapless::consts::U4;
+
+/// A fixed-capacity queue of events posted by widgets.
+pub type Queue = heapless::Vec<Event, Capacity>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Pointer(Pointer),
+    Navigation(Navigation),
+    Gesture(Gesture),
+    Edit(Edit),
+    /// The pointer moved to a point while not pressed, which only pointing devices that can hover
+    /// report, e.g. a mouse.
+    ///
+    /// It is not delivered to any widget, but changes which widget is hovered; movement while
+    /// pressed is reported as a `Pointer` event instead.
+    Hover(embedded_graphics::geometry::Point),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pointer {
+    pub action: PointerAction,
+    pub position: embedded_graphics::geometry::Point,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerAction {
+    Down,
+    Move,
+    Up,
+}
+
+/// Events from keys or rotary encoders, which are delivered to the focused widget.
+///
+/// If the focused widget does not handle the event, it moves the focus instead.  Widgets whose
+/// value is changed with the arrow keys, such as sliders, leave them unhandled once the value can't
+/// change any further in that direction, so that they move the focus on from there.  Rotary
+/// encoders only emit `Next`, `Previous` and `Activate`, so such widgets also have an editing mode
+/// that `Activate` toggles, in which `Next` and `Previous` change the value instead of moving the
+/// focus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Navigation {
+    /// Moves focus to the next widget in tree order.
+    Next,
+    /// Moves focus to the previous widget in tree order.
+    Previous,
+    /// Activates the focused widget, e.g. presses a button.
+    Activate,
+    /// Goes back from the focused widget, e.g. leaves a submenu.
+    ///
+    /// Emitted by the escape key of the simulator, and by a `gpio::Button` created with it.
+    Back,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Text editing events from a keyboard, which are delivered to the focused widget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// Inserts a character at the cursor, replacing the selection if there is one.
+    Insert(char),
+    /// Deletes the selection, or the character before the cursor.
+    Backspace,
+    /// Deletes the selection, or the character after the cursor.
+    Delete,
+    /// Moves the cursor, extending the selection if `select` is true and clearing it otherwise.
+    Move {
+        motion: Motion,
+        select: bool,
+    },
+    SelectAll,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Motion {
+    Left,
+    Right,
+    Home,
+    End,
+}
+
+/// Gestures recognized from pointer events by a `gesture::Recognizer`.
+///
+/// Gestures are delivered to the widget under the point where they started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    Tap {
+        position: embedded_graphics::geometry::Point,
+    },
+    DoubleTap {
+        position: embedded_graphics::geometry::Point,
+    },
+    /// A touch held in place; repeated while it is held if `gesture::Config::long_press_repeat` is
+    /// set.
+    LongPress {
+        position: embedded_graphics::geometry::Point,
+    },
+    Swipe {
+        start: embedded_graphics::geometry::Point,
+        end: embedded_graphics::geometry::Point,
+        direction: Direction,
+        /// The velocity along `direction`, in pixels per second.
+        velocity: f32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The phase of delivering an event along the path from the root widget to its target.
+///
+/// Delivery stops as soon as a widget handles the event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// The event is on its way down to the target, and ancestors may intercept it, starting with
+    /// the root.
+    Capture,
+    /// The event is delivered to its target.
+    Target,
+    /// The target did not handle the event, so ancestors may handle it instead, starting with the
+    /// parent of the target.
+    Bubble,
+}
+
+impl Pointer {
+    pub fn new(action: PointerAction, position: embedded_graphics::geometry::Point) -> Self {
+        Self { action, position }
+    }
+}
+
+impl From<Pointer> for Event {
+    fn from(pointer: Pointer) -> Self {
+        Event::Pointer(pointer)
+    }
+}
+
+impl Gesture {
+    /// The point where the gesture started.
+    pub fn position(&self) -> embedded_graphics::geometry::Point {
+        match *self {
+            Gesture::Tap { position }
+            | Gesture::DoubleTap { position }
+            | Gesture::LongPress { position } => position,
+            Gesture::Swipe { start, .. } => start,
+        }
+    }
+}
+
+impl From<Navigation> for Event {
+    fn from(navigation: Navigation) -> Self {
+        Event::Navigation(navigation)
+    }
+}
+
+impl From<Edit> for Event {
+    fn from(edit: Edit) -> Self {
+        Event::Edit(edit)
+    }
+}
+
+impl From<Gesture> for Event {
+    fn from(gesture: Gesture) -> Self {
+        Event::Gesture(gesture)
+    }
+}
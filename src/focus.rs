@@ -0,0 +1,467 @@
+//! Keyboard and rotary encoder focus navigation.
+//!
+//! Focus moves between the widgets that declare themselves focusable via
+//! `widget::Widget::is_focusable`, either in tree order or spatially based on the geometry computed
+//! during the last layout pass.  While a modal widget such as an open dialog is shown, the focus is
+//! confined to it.
+use crate::event;
+use crate::input;
+use crate::layout;
+use crate::theme;
+use crate::widget;
+
+/// Finds the widget that should receive focus when moving away from `current`.
+///
+/// Tree order navigation wraps around at the ends.  Spatial navigation picks the closest focusable
+/// widget in the requested direction, preferring widgets that are well aligned with the current
+/// one, and stays put if there is no such widget.  Only widgets in the topmost modal widget are
+/// considered, if there is one; see `input::modal`.
+pub fn find<'a, T, M, N>(
+    layout: &layout::Layout,
+    widget: &N,
+    current: Option<widget::Id>,
+    navigation: event::Navigation,
+) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let scope = input::modal(layout, widget);
+    match navigation {
+        event::Navigation::Next => find_next(layout, widget, scope, current),
+        event::Navigation::Previous => find_previous(layout, widget, scope, current),
+        event::Navigation::Up
+        | event::Navigation::Down
+        | event::Navigation::Left
+        | event::Navigation::Right => find_nearest(layout, widget, scope, current, navigation),
+        event::Navigation::Activate | event::Navigation::Back => current,
+    }
+}
+
+/// The widget that should have the focus instead of `current` while the topmost modal widget is
+/// shown, so that navigation and editing events don't go to the widgets behind it.
+///
+/// The focus stays where it is if it is in the modal widget or on one of its ancestors, such as a
+/// dropdown whose list is open.  Otherwise it moves to the first focusable widget in the modal
+/// widget, or to the modal widget itself if there is none, so that its ancestors still get to
+/// handle the events.
+pub fn confine<'a, T, M, N>(
+    layout: &layout::Layout,
+    widget: &N,
+    current: Option<widget::Id>,
+) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let modal = match input::modal(layout, widget) {
+        Some(modal) => modal,
+        None => return current,
+    };
+
+    // One of the widgets is on the path to the other if they are in the same subtree
+    let on_path = |target: widget::Id, id: widget::Id| {
+        input::path(widget, target).map_or(false, |path| path.contains(&id))
+    };
+    if let Some(current) = current {
+        if on_path(modal, current) || on_path(current, modal) {
+            return Some(current);
+        }
+    }
+
+    let mut first = None;
+    for_each_focusable_in(layout, widget, Some(modal), |id, _| {
+        if first.is_none() {
+            first = Some(id);
+        }
+    });
+    first.or(Some(modal))
+}
+
+fn find_next<'a, T, M, N>(
+    layout: &layout::Layout,
+    widget: &N,
+    scope: Option<widget::Id>,
+    current: Option<widget::Id>,
+) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let mut first = None;
+    let mut next = None;
+    let mut seen_current = false;
+
+    for_each_focusable_in(layout, widget, scope, |id, _| {
+        if first.is_none() {
+            first = Some(id);
+        }
+        if seen_current && next.is_none() {
+            next = Some(id);
+        }
+        if Some(id) == current {
+            seen_current = true;
+        }
+    });
+
+    next.or(first)
+}
+
+fn find_previous<'a, T, M, N>(
+    layout: &layout::Layout,
+    widget: &N,
+    scope: Option<widget::Id>,
+    current: Option<widget::Id>,
+) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let mut last = None;
+    let mut previous = None;
+    let mut seen_current = false;
+
+    for_each_focusable_in(layout, widget, scope, |id, _| {
+        if Some(id) == current {
+            seen_current = true;
+            previous = last;
+        }
+        last = Some(id);
+    });
+
+    if seen_current {
+        previous.or(last)
+    } else {
+        last
+    }
+}
+
+fn find_nearest<'a, T, M, N>(
+    layout: &layout::Layout,
+    widget: &N,
+    scope: Option<widget::Id>,
+    current: Option<widget::Id>,
+    navigation: event::Navigation,
+) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let mut origin = None;
+    for_each_focusable_in(layout, widget, scope, |id, geometry| {
+        if Some(id) == current {
+            origin = Some(center(geometry));
+        }
+    });
+
+    let origin = match origin {
+        Some(origin) => origin,
+        None => return find_next(layout, widget, scope, current),
+    };
+
+    let mut best: Option<(f32, widget::Id)> = None;
+    for_each_focusable_in(layout, widget, scope, |id, geometry| {
+        let target = center(geometry);
+        let dx = target.x - origin.x;
+        let dy = target.y - origin.y;
+
+        let (along, across) = match navigation {
+            event::Navigation::Up => (-dy, dx),
+            event::Navigation::Down => (dy, dx),
+            event::Navigation::Left => (-dx, dy),
+            event::Navigation::Right => (dx, dy),
+            _ => return,
+        };
+
+        if Some(id) != current && along > 0.0 {
+            // Misalignment counts double so that focus moves in straight lines where possible
+            let score = along + 2.0 * across.abs();
+            if best.map_or(true, |(best_score, _)| score < best_score) {
+                best = Some((score, id));
+            }
+        }
+    });
+
+    best.map(|(_, id)| id).or(current)
+}
+
+fn center(geometry: layout::Geometry) -> layout::Point {
+    layout::Point {
+        x: geometry.position.x + geometry.size.width / 2.0,
+        y: geometry.position.y + geometry.size.height / 2.0,
+    }
+}
+
+/// Calls `f` with the identity and absolute geometry of every focusable widget, in tree order.
+pub fn for_each_focusable<'a, T, M, N, F>(layout: &layout::Layout, widget: &N, f: F)
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+    F: FnMut(widget::Id, layout::Geometry),
+{
+    for_each_focusable_in(layout, widget, None, f);
+}
+
+/// Like `for_each_focusable`, but only for the widget identified by `scope` and its descendants,
+/// if given.
+fn for_each_focusable_in<'a, T, M, N, F>(
+    layout: &layout::Layout,
+    widget: &N,
+    scope: Option<widget::Id>,
+    mut f: F,
+) where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+    F: FnMut(widget::Id, layout::Geometry),
+{
+    let offset = layout::Point { x: 0.0, y: 0.0 };
+    let inside = scope.is_none();
+    let f = &mut f;
+    widget.visit(FocusableVisitor {
+        layout,
+        offset,
+        scope,
+        inside,
+        f,
+    });
+}
+
+struct FocusableVisitor<'a, F> {
+    layout: &'a layout::Layout,
+    offset: layout::Point,
+    scope: Option<widget::Id>,
+    /// Whether the widget is in the scope.
+    inside: bool,
+    f: &'a mut F,
+}
+
+struct FocusableChildVisitor<'a, F> {
+    layout: &'a layout::Layout,
+    offset: Option<layout::Point>,
+    scope: Option<widget::Id>,
+    inside: bool,
+    f: &'a mut F,
+}
+
+impl<'a, F> FocusableVisitor<'a, F>
+where
+    F: FnMut(widget::Id, layout::Geometry),
+{
+    fn visit_widget<'b, T, M, N>(&mut self, node: &N) -> Option<layout::Point>
+    where
+        T: theme::Theme<'b>,
+        N: widget::Widget<'b, T, M>,
+    {
+        let state = node.layout_state();
+        let geometry = self.layout.get_geometry(state)?.translate(self.offset);
+        self.inside |= self.scope.is_some() && state.id() == self.scope;
+
+        if self.inside && node.is_focusable() {
+            if let Some(id) = state.id() {
+                (self.f)(id, geometry);
+            }
+        }
+
+        Some(geometry.child_origin(node.scroll_offset()))
+    }
+}
+
+impl<'a, 'b, T, M, F> widget::Visitor<'b, T, M> for FocusableVisitor<'a, F>
+where
+    T: theme::Theme<'b>,
+    F: FnMut(widget::Id, layout::Geometry),
+{
+    type Output = ();
+    type ChildVisitor = FocusableChildVisitor<'a, F>;
+
+    fn accept_leaf<N>(mut self, node: &N) -> Self::Output
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        self.visit_widget(node);
+    }
+
+    fn accept_node<N>(mut self, node: &N, _child_count: usize) -> Self::ChildVisitor
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        // Widgets that have never been laid out have no geometry, so skip their children too
+        let offset = self.visit_widget(node);
+        let layout = self.layout;
+        let scope = self.scope;
+        let inside = self.inside;
+        let f = self.f;
+
+        FocusableChildVisitor {
+            layout,
+            offset,
+            scope,
+            inside,
+            f,
+        }
+    }
+}
+
+impl<'a, 'b, T, M, F> widget::ChildVisitor<'b, T, M> for FocusableChildVisitor<'a, F>
+where
+    T: theme::Theme<'b>,
+    F: FnMut(widget::Id, layout::Geometry),
+{
+    type Output = ();
+
+    fn accept_child<W>(&mut self, widget: &W)
+    where
+        W: widget::Node<'b, T, M>,
+    {
+        if let Some(offset) = self.offset {
+            let layout = self.layout;
+            let scope = self.scope;
+            let inside = self.inside;
+            let f = &mut *self.f;
+            widget.visit(FocusableVisitor {
+                layout,
+                offset,
+                scope,
+                inside,
+                f,
+            });
+        }
+    }
+
+    fn end(self) -> Self::Output {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, Probe};
+
+    /// Four focusable widgets in a grid, in the order `a`, `b`, `c`, `d`:
+    ///
+    /// ```text
+    /// a b
+    /// c d
+    /// ```
+    type Grid = Probe<(
+        Probe<testing::Leaf>,
+        Probe<testing::Leaf>,
+        Probe<testing::Leaf>,
+        Probe<testing::Leaf>,
+    )>;
+
+    fn grid() -> Grid {
+        Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 40.0, 40.0).focusable(),
+            Probe::new('b', 60.0, 0.0, 40.0, 40.0).focusable(),
+            Probe::new('c', 0.0, 60.0, 40.0, 40.0).focusable(),
+            Probe::new('d', 60.0, 60.0, 40.0, 40.0).focusable(),
+        ))
+    }
+
+    #[test]
+    fn tree_order() {
+        let mut tree = grid();
+        let layout = testing::layout(&mut tree);
+        let (a, b, c, d) = {
+            let (a, b, c, d) = &tree.children;
+            (a.id(), b.id(), c.id(), d.id())
+        };
+        let next = |current| find(&layout, &tree, current, event::Navigation::Next);
+        let previous = |current| find(&layout, &tree, current, event::Navigation::Previous);
+
+        assert_eq!(next(None), Some(a));
+        assert_eq!(next(Some(a)), Some(b));
+        assert_eq!(next(Some(c)), Some(d));
+        assert_eq!(next(Some(d)), Some(a));
+        assert_eq!(previous(None), Some(d));
+        assert_eq!(previous(Some(b)), Some(a));
+        assert_eq!(previous(Some(a)), Some(d));
+        // Widgets that aren't focusable are skipped
+        assert_eq!(next(Some(tree.id())), Some(a));
+    }
+
+    #[test]
+    fn spatial() {
+        let mut tree = grid();
+        let layout = testing::layout(&mut tree);
+        let (a, b, c, d) = {
+            let (a, b, c, d) = &tree.children;
+            (a.id(), b.id(), c.id(), d.id())
+        };
+        let go = |current, navigation| find(&layout, &tree, Some(current), navigation);
+
+        assert_eq!(go(a, event::Navigation::Right), Some(b));
+        assert_eq!(go(a, event::Navigation::Down), Some(c));
+        assert_eq!(go(d, event::Navigation::Up), Some(b));
+        assert_eq!(go(d, event::Navigation::Left), Some(c));
+        // There is nothing further in that direction
+        assert_eq!(go(a, event::Navigation::Left), Some(a));
+        assert_eq!(go(a, event::Navigation::Up), Some(a));
+        assert_eq!(go(a, event::Navigation::Activate), Some(a));
+    }
+
+    #[test]
+    fn spatial_prefers_aligned_widgets() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 40.0, 20.0, 20.0).focusable(),
+            Probe::new('b', 30.0, 0.0, 20.0, 20.0).focusable(),
+            Probe::new('c', 60.0, 40.0, 20.0, 20.0).focusable(),
+        ));
+        let layout = testing::layout(&mut tree);
+        let (a, _, c) = &tree.children;
+
+        let found = find(&layout, &tree, Some(a.id()), event::Navigation::Right);
+        assert_eq!(found, Some(c.id()));
+    }
+
+    #[test]
+    fn modal_widgets_confine_the_focus() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 20.0, 20.0).focusable(),
+            Probe::new('o', 20.0, 20.0, 60.0, 60.0)
+                .layer(2)
+                .modal()
+                .children((
+                    Probe::new('x', 0.0, 0.0, 20.0, 20.0).focusable(),
+                    Probe::new('y', 30.0, 0.0, 20.0, 20.0).focusable(),
+                )),
+        ));
+        let layout = testing::layout(&mut tree);
+        let a = tree.children.0.id();
+        let (x, y) = {
+            let (x, y) = &tree.children.1.children;
+            (x.id(), y.id())
+        };
+
+        assert_eq!(confine(&layout, &tree, Some(a)), Some(x));
+        assert_eq!(confine(&layout, &tree, None), Some(x));
+        assert_eq!(confine(&layout, &tree, Some(y)), Some(y));
+
+        // Navigation stays within the modal widget
+        let go = |current, navigation| find(&layout, &tree, Some(current), navigation);
+        assert_eq!(go(x, event::Navigation::Next), Some(y));
+        assert_eq!(go(y, event::Navigation::Next), Some(x));
+        assert_eq!(go(x, event::Navigation::Previous), Some(y));
+        assert_eq!(go(y, event::Navigation::Left), Some(x));
+        assert_eq!(go(x, event::Navigation::Left), Some(x));
+    }
+
+    #[test]
+    fn modal_widgets_keep_the_focus_on_their_ancestors() {
+        // Like a dropdown with its list open
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 20.0, 20.0).focusable(),
+            Probe::new('p', 0.0, 50.0, 50.0, 20.0)
+                .focusable()
+                .children((Probe::new('l', 0.0, 20.0, 50.0, 30.0).layer(1).modal(),)),
+        ));
+        let layout = testing::layout(&mut tree);
+        let a = tree.children.0.id();
+        let p = tree.children.1.id();
+        let list = tree.children.1.children.0.id();
+
+        assert_eq!(confine(&layout, &tree, Some(p)), Some(p));
+        // Without anything focusable in it, the modal widget itself gets the focus
+        assert_eq!(confine(&layout, &tree, Some(a)), Some(list));
+    }
+}
@@ -0,0 +1,373 @@
+//! Recognition of gestures from raw pointer events.
+//!
+//! A `Recognizer` is fed with the pointer events from a touch panel along with the time at which
+//! they happened, and produces `event::Gesture` events which are then delivered to the widget under
+//! the point where the gesture started.  The recognizer only keeps a fixed amount of state, so it
+//! needs no allocator.
+use crate::event;
+use core::time;
+
+/// Thresholds used to tell gestures apart.
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// How far, in pixels, the pointer may move before a touch stops counting as a tap.
+    pub slop: u32,
+    /// How long a touch must be held in place to count as a long press.
+    pub long_press: time::Duration,
+    /// How often a long press is reported again while the touch is still held, like a held key
+    /// repeats, e.g. for stepper buttons; defaults to `None`, which reports it only once.
+    pub long_press_repeat: Option<time::Duration>,
+    /// The maximum time between two taps for them to count as a double tap.
+    pub double_tap: time::Duration,
+    /// The minimum velocity, in pixels per second, for a movement to count as a swipe.
+    pub swipe_velocity: f32,
+}
+
+#[derive(Debug)]
+pub struct Recognizer {
+    config: Config,
+    touch: Option<Touch>,
+    last_tap: Option<Tap>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Touch {
+    start: embedded_graphics::geometry::Point,
+    start_time: time::Duration,
+    moved: bool,
+    /// When the touch was last reported as a long press, if it was.
+    long_pressed: Option<time::Duration>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Tap {
+    position: embedded_graphics::geometry::Point,
+    time: time::Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            slop: 8,
+            long_press: time::Duration::from_millis(500),
+            long_press_repeat: None,
+            double_tap: time::Duration::from_millis(300),
+            swipe_velocity: 200.0,
+        }
+    }
+}
+
+impl Recognizer {
+    pub fn new(config: Config) -> Self {
+        let touch = None;
+        let last_tap = None;
+        Self {
+            config,
+            touch,
+            last_tap,
+        }
+    }
+
+    /// Feeds a pointer event that happened at time `now` to the recognizer.
+    ///
+    /// The first tap is reported as a tap right away, so that taps are never delayed while waiting
+    /// to see whether a second one follows.  The second tap of a double tap is reported as a double
+    /// tap instead of a tap, so widgets that count taps should count a double tap as one more.
+    pub fn update(
+        &mut self,
+        pointer: event::Pointer,
+        now: time::Duration,
+    ) -> Option<event::Gesture> {
+        match pointer.action {
+            event::PointerAction::Down => {
+                self.touch = Some(Touch {
+                    start: pointer.position,
+                    start_time: now,
+                    moved: false,
+                    long_pressed: None,
+                });
+                None
+            }
+            event::PointerAction::Move => {
+                let slop = self.config.slop;
+                if let Some(touch) = &mut self.touch {
+                    if !within(touch.start, pointer.position, slop) {
+                        touch.moved = true;
+                    }
+                }
+                None
+            }
+            event::PointerAction::Up => {
+                let touch = self.touch.take()?;
+                if touch.long_pressed.is_some() {
+                    None
+                } else if touch.moved || !within(touch.start, pointer.position, self.config.slop) {
+                    self.swipe(touch, pointer.position, now)
+                } else {
+                    Some(self.tap(touch.start, now))
+                }
+            }
+        }
+    }
+
+    /// Checks for gestures that are recognized by time passing, i.e. long presses and their
+    /// repeats.
+    ///
+    /// This should be called regularly, e.g. once per frame.
+    pub fn poll(&mut self, now: time::Duration) -> Option<event::Gesture> {
+        let Config {
+            long_press,
+            long_press_repeat,
+            ..
+        } = self.config;
+        let touch = self.touch.as_mut()?;
+
+        let due = match (touch.long_pressed, long_press_repeat) {
+            _ if touch.moved => false,
+            (None, _) => elapsed(touch.start_time, now) >= long_press,
+            (Some(last), Some(repeat)) => elapsed(last, now) >= repeat,
+            (Some(_), None) => false,
+        };
+        if due {
+            touch.long_pressed = Some(now);
+            self.last_tap = None;
+            Some(event::Gesture::LongPress {
+                position: touch.start,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn tap(
+        &mut self,
+        position: embedded_graphics::geometry::Point,
+        now: time::Duration,
+    ) -> event::Gesture {
+        let is_double_tap = self.last_tap.map_or(false, |tap| {
+            elapsed(tap.time, now) <= self.config.double_tap
+                && within(tap.position, position, 2 * self.config.slop)
+        });
+
+        if is_double_tap {
+            self.last_tap = None;
+            event::Gesture::DoubleTap { position }
+        } else {
+            self.last_tap = Some(Tap {
+                position,
+                time: now,
+            });
+            event::Gesture::Tap { position }
+        }
+    }
+
+    fn swipe(
+        &mut self,
+        touch: Touch,
+        end: embedded_graphics::geometry::Point,
+        now: time::Duration,
+    ) -> Option<event::Gesture> {
+        self.last_tap = None;
+
+        let start = touch.start;
+        let dx = end.x - start.x;
+        let dy = end.y - start.y;
+
+        // Only the movement along the swipe direction counts towards its velocity
+        let seconds = elapsed(touch.start_time, now).as_millis().max(1) as f32 / 1000.0;
+        let velocity = dx.abs().max(dy.abs()) as f32 / seconds;
+
+        if velocity < self.config.swipe_velocity {
+            return None;
+        }
+
+        let direction = if dx.abs() >= dy.abs() {
+            if dx > 0 {
+                event::Direction::Right
+            } else {
+                event::Direction::Left
+            }
+        } else if dy > 0 {
+            event::Direction::Down
+        } else {
+            event::Direction::Up
+        };
+
+        Some(event::Gesture::Swipe {
+            start,
+            end,
+            direction,
+            velocity,
+        })
+    }
+}
+
+impl Default for Recognizer {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
+
+fn within(
+    a: embedded_graphics::geometry::Point,
+    b: embedded_graphics::geometry::Point,
+    radius: u32,
+) -> bool {
+    let dx = i64::from(b.x - a.x);
+    let dy = i64::from(b.y - a.y);
+    let radius = i64::from(radius);
+    dx * dx + dy * dy <= radius * radius
+}
+
+fn elapsed(since: time::Duration, now: time::Duration) -> time::Duration {
+    now.checked_sub(since).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: i32, y: i32) -> embedded_graphics::geometry::Point {
+        embedded_graphics::geometry::Point::new(x, y)
+    }
+
+    fn pointer(action: event::PointerAction, x: i32, y: i32) -> event::Pointer {
+        event::Pointer {
+            action,
+            position: point(x, y),
+        }
+    }
+
+    fn ms(millis: u64) -> time::Duration {
+        time::Duration::from_millis(millis)
+    }
+
+    fn tap(recognizer: &mut Recognizer, x: i32, y: i32, at: u64) -> Option<event::Gesture> {
+        let down = recognizer.update(pointer(event::PointerAction::Down, x, y), ms(at));
+        assert_eq!(down, None);
+        recognizer.update(pointer(event::PointerAction::Up, x, y), ms(at + 50))
+    }
+
+    #[test]
+    fn tap_and_double_tap() {
+        let mut recognizer = Recognizer::default();
+        let position = point(10, 10);
+        assert_eq!(
+            tap(&mut recognizer, 10, 10, 0),
+            Some(event::Gesture::Tap { position })
+        );
+        assert_eq!(
+            tap(&mut recognizer, 11, 10, 200),
+            Some(event::Gesture::DoubleTap {
+                position: point(11, 10)
+            })
+        );
+        // A third tap starts over
+        assert_eq!(
+            tap(&mut recognizer, 10, 10, 400),
+            Some(event::Gesture::Tap { position })
+        );
+    }
+
+    #[test]
+    fn slow_taps_are_not_a_double_tap() {
+        let mut recognizer = Recognizer::default();
+        let position = point(10, 10);
+        assert_eq!(
+            tap(&mut recognizer, 10, 10, 0),
+            Some(event::Gesture::Tap { position })
+        );
+        assert_eq!(
+            tap(&mut recognizer, 10, 10, 1000),
+            Some(event::Gesture::Tap { position })
+        );
+    }
+
+    #[test]
+    fn distant_taps_are_not_a_double_tap() {
+        let mut recognizer = Recognizer::default();
+        assert_eq!(
+            tap(&mut recognizer, 10, 10, 0),
+            Some(event::Gesture::Tap {
+                position: point(10, 10)
+            })
+        );
+        assert_eq!(
+            tap(&mut recognizer, 100, 10, 100),
+            Some(event::Gesture::Tap {
+                position: point(100, 10)
+            })
+        );
+    }
+
+    #[test]
+    fn long_press() {
+        let mut recognizer = Recognizer::default();
+        recognizer.update(pointer(event::PointerAction::Down, 5, 5), ms(0));
+        assert_eq!(recognizer.poll(ms(100)), None);
+        assert!(recognizer.poll(ms(600)).is_some());
+        assert_eq!(recognizer.poll(ms(2000)), None);
+        let up = recognizer.update(pointer(event::PointerAction::Up, 5, 5), ms(2100));
+        assert_eq!(up, None);
+    }
+
+    #[test]
+    fn repeated_long_press() {
+        let mut recognizer = Recognizer::new(Config {
+            long_press_repeat: Some(ms(100)),
+            ..Config::default()
+        });
+        recognizer.update(pointer(event::PointerAction::Down, 5, 5), ms(0));
+        assert_eq!(recognizer.poll(ms(100)), None);
+        assert_eq!(
+            recognizer.poll(ms(600)),
+            Some(event::Gesture::LongPress {
+                position: point(5, 5)
+            })
+        );
+        // Repeated while held, and not followed by a tap
+        assert_eq!(recognizer.poll(ms(650)), None);
+        assert_eq!(
+            recognizer.poll(ms(700)),
+            Some(event::Gesture::LongPress {
+                position: point(5, 5)
+            })
+        );
+        let up = recognizer.update(pointer(event::PointerAction::Up, 5, 5), ms(750));
+        assert_eq!(up, None);
+        assert_eq!(recognizer.poll(ms(800)), None);
+    }
+
+    #[test]
+    fn moving_prevents_long_press() {
+        let mut recognizer = Recognizer::default();
+        recognizer.update(pointer(event::PointerAction::Down, 5, 5), ms(0));
+        recognizer.update(pointer(event::PointerAction::Move, 50, 5), ms(100));
+        assert_eq!(recognizer.poll(ms(600)), None);
+    }
+
+    #[test]
+    fn swipe() {
+        let mut recognizer = Recognizer::default();
+        recognizer.update(pointer(event::PointerAction::Down, 100, 50), ms(0));
+        recognizer.update(pointer(event::PointerAction::Move, 60, 52), ms(50));
+        let gesture = recognizer.update(pointer(event::PointerAction::Up, 20, 55), ms(100));
+        assert_eq!(
+            gesture,
+            Some(event::Gesture::Swipe {
+                start: point(100, 50),
+                end: point(20, 55),
+                direction: event::Direction::Left,
+                velocity: 800.0,
+            })
+        );
+    }
+
+    #[test]
+    fn slow_movement_is_not_a_swipe() {
+        let mut recognizer = Recognizer::default();
+        recognizer.update(pointer(event::PointerAction::Down, 0, 0), ms(0));
+        let gesture = recognizer.update(pointer(event::PointerAction::Up, 0, 40), ms(1000));
+        assert_eq!(gesture, None);
+    }
+}
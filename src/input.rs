@@ -0,0 +1,971 @@
+//! Delivery of input events to the widgets they are meant for.
+//!
+//! Events are delivered in several passes over the widget tree: first the target widget is located
+//! (e.g. by hit testing a pointer position against the geometry of the last layout pass), and
+//! then the tree is walked again mutably to let the widgets along the path to the target handle the
+//! event, in the order described by `event::Phase`.
+//!
+//! The submodules contain `Source` implementations for common kinds of input hardware.  Several
+//! sources can be combined into one by putting them in a tuple, and touch panels can be calibrated
+//! by wrapping their source in a `calibration::Calibrated`.
+use crate::event;
+use crate::layout;
+use crate::message;
+use crate::theme;
+use crate::widget;
+use core::time;
+
+/// The maximum depth of the widget tree that events are delivered through; if the target of an
+/// event is nested deeper than this, its outermost ancestors don't get to capture or bubble it.
+pub type MaxDepth = heapless::consts::U16;
+
+/// The widgets from the root down to some widget in the tree, or only the deepest of them; see
+/// `path`.
+pub type Path = heapless::Vec<widget::Id, MaxDepth>;
+
+pub mod calibration;
+pub mod encoder;
+pub mod gpio;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+
+/// A source of input events, e.g. a touch controller or a set of buttons.
+pub trait Source {
+    /// Returns the next pending event, or `None` if there are no more events right now.
+    ///
+    /// `now` is the current time, as returned by the application clock, which sources can use for
+    /// e.g. debouncing.
+    fn poll(&mut self, now: time::Duration) -> Option<event::Event>;
+}
+
+impl Source for core::iter::Empty<event::Event> {
+    fn poll(&mut self, _now: time::Duration) -> Option<event::Event> {
+        None
+    }
+}
+
+macro_rules! tuple_sources {
+    ($($S:ident),*) => {
+        impl<$($S),*> Source for ($($S,)*)
+        where
+            $($S: Source,)*
+        {
+            #[allow(non_snake_case)]
+            fn poll(&mut self, now: time::Duration) -> Option<event::Event> {
+                match *self {
+                    ($(ref mut $S,)*) => {
+                        $(if let Some(event) = $S.poll(now) {
+                            return Some(event);
+                        })*
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+tuple_sources!(S1);
+tuple_sources!(S1, S2);
+tuple_sources!(S1, S2, S3);
+tuple_sources!(S1, S2, S3, S4);
+tuple_sources!(S1, S2, S3, S4, S5);
+tuple_sources!(S1, S2, S3, S4, S5, S6);
+tuple_sources!(S1, S2, S3, S4, S5, S6, S7);
+tuple_sources!(S1, S2, S3, S4, S5, S6, S7, S8);
+
+/// Finds the deepest widget under `point`.
+///
+/// Widgets in higher layers are drawn on top of those in lower layers, and later siblings on top
+/// of earlier ones, so they win if several widgets overlap; see `widget::Widget::layer`.  If the
+/// topmost modal widget is in a higher layer than the widget under `point`, it is returned instead.
+pub fn hit_test<'a, T, M, N>(
+    layout: &layout::Layout,
+    widget: &N,
+    point: embedded_graphics::geometry::Point,
+) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let offset = layout::Point { x: 0.0, y: 0.0 };
+    let point = point.into();
+    let layer = 0;
+    let clip = None;
+    let hit = widget.visit(HitTestVisitor {
+        layout,
+        offset,
+        point,
+        layer,
+        clip,
+    });
+
+    match (hit.widget, hit.modal) {
+        (Some((layer, id)), Some((modal_layer, _))) if layer >= modal_layer => Some(id),
+        (_, Some((_, modal))) => Some(modal),
+        (widget, None) => widget.map(|(_, id)| id),
+    }
+}
+
+/// Finds the topmost modal widget, e.g. the overlay of an open dialog; see
+/// `widget::Widget::is_modal`.
+pub fn modal<'a, T, M, N>(layout: &layout::Layout, widget: &N) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    // Which widgets are modal doesn't depend on the point being hit tested
+    let offset = layout::Point { x: 0.0, y: 0.0 };
+    let point = offset;
+    let layer = 0;
+    let clip = None;
+    let hit = widget.visit(HitTestVisitor {
+        layout,
+        offset,
+        point,
+        layer,
+        clip,
+    });
+    hit.modal.map(|(_, id)| id)
+}
+
+/// Finds the path from the root widget down to the widget identified by `target`.
+///
+/// If the target is nested deeper than `MaxDepth`, the path only contains its closest ancestors,
+/// i.e. the outermost ones are dropped.
+pub fn path<'a, T, M, N>(widget: &N, target: widget::Id) -> Option<Path>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let depth = widget.visit(DepthVisitor { target, depth: 0 })?;
+    let mut path = Path::new();
+    let skip = (depth + 1).saturating_sub(path.capacity());
+    let found = widget.visit(PathVisitor {
+        target,
+        path: &mut path,
+        depth: 0,
+        skip,
+    });
+    if found {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Delivers `event` to the widget identified by `target` and its ancestors, returning the widget
+/// that handled it, if any.
+///
+/// The ancestors first get to capture the event, then the target handles it, and finally it bubbles
+/// back up through the ancestors; see `event::Phase`.  All phases are delivered in a single walk
+/// down the path to the target and back up.  `now` is the time at which the event happened.  Any
+/// messages emitted by the widgets are pushed onto `messages`, and any events that they post onto
+/// `events`.
+#[allow(clippy::too_many_arguments)]
+pub fn dispatch<'a, T, M, N>(
+    layout: &layout::Layout,
+    theme: &T,
+    widget: &mut N,
+    target: widget::Id,
+    event: &event::Event,
+    now: time::Duration,
+    messages: &mut message::Queue<M>,
+    events: &mut event::Queue,
+) -> Option<widget::Id>
+where
+    T: theme::Theme<'a>,
+    N: widget::Node<'a, T, M>,
+{
+    let path = path(widget, target)?;
+    let mut delivery = Delivery {
+        layout,
+        theme,
+        event,
+        now,
+        messages,
+        events,
+    };
+
+    let offset = layout::Point { x: 0.0, y: 0.0 };
+    let outcome = widget.visit_mut(DispatchVisitor {
+        delivery: &mut delivery,
+        path: &path,
+        index: 0,
+        offset,
+    });
+
+    if outcome.ancestor && outcome.handler.is_none() {
+        widget.visit_mut(BubbleVisitor {
+            delivery: &mut delivery,
+            offset,
+        })
+    } else {
+        outcome.handler
+    }
+}
+
+struct HitTestVisitor<'a> {
+    layout: &'a layout::Layout,
+    offset: layout::Point,
+    point: layout::Point,
+    layer: u8,
+    clip: Option<layout::Geometry>,
+}
+
+struct HitTestChildVisitor<'a> {
+    layout: &'a layout::Layout,
+    offset: Option<layout::Point>,
+    point: layout::Point,
+    layer: u8,
+    clip: Option<layout::Geometry>,
+    hit: Hit,
+}
+
+/// The topmost widget under the point and the topmost modal widget found so far, with their
+/// layers.
+#[derive(Clone, Copy, Debug, Default)]
+struct Hit {
+    widget: Option<(u8, widget::Id)>,
+    modal: Option<(u8, widget::Id)>,
+}
+
+struct DepthVisitor {
+    target: widget::Id,
+    depth: usize,
+}
+
+struct DepthChildVisitor {
+    target: widget::Id,
+    depth: usize,
+    found: Option<usize>,
+}
+
+struct PathVisitor<'a> {
+    target: widget::Id,
+    path: &'a mut Path,
+    depth: usize,
+    /// How many of the outermost widgets are left out of the path so that the target fits.
+    skip: usize,
+}
+
+struct PathChildVisitor<'a> {
+    target: widget::Id,
+    path: &'a mut Path,
+    depth: usize,
+    skip: usize,
+    entered: bool,
+    found: bool,
+}
+
+/// What is needed to let widgets handle an event.
+struct Delivery<'a, T, M> {
+    layout: &'a layout::Layout,
+    theme: &'a T,
+    event: &'a event::Event,
+    now: time::Duration,
+    messages: &'a mut message::Queue<M>,
+    events: &'a mut event::Queue,
+}
+
+/// Follows the path to the target, letting the ancestors capture the event on the way down and
+/// bubbling it up to them on the way back.
+struct DispatchVisitor<'v, 'a, T, M> {
+    delivery: &'v mut Delivery<'a, T, M>,
+    path: &'v [widget::Id],
+    /// The index in `path` of the widget that is expected next.
+    index: usize,
+    offset: layout::Point,
+}
+
+struct DispatchChildVisitor<'v, 'a, T, M> {
+    delivery: &'v mut Delivery<'a, T, M>,
+    path: &'v [widget::Id],
+    index: usize,
+    offset: layout::Point,
+    /// Whether the children may contain the target.
+    search: bool,
+    outcome: Outcome,
+}
+
+/// Delivers the event to a widget in the bubble phase, without visiting its children.
+struct BubbleVisitor<'v, 'a, T, M> {
+    delivery: &'v mut Delivery<'a, T, M>,
+    offset: layout::Point,
+}
+
+struct BubbleChildVisitor {
+    handler: Option<widget::Id>,
+}
+
+/// The result of delivering an event to a subtree.
+#[derive(Clone, Copy, Debug, Default)]
+struct Outcome {
+    /// Whether the target is in the subtree, so that the rest of the tree need not be visited.
+    found: bool,
+    /// The widget that handled the event, if any.
+    handler: Option<widget::Id>,
+    /// Whether the root of the subtree is an ancestor of the target, to which the event bubbles up
+    /// if no widget below it handled the event.
+    ancestor: bool,
+}
+
+impl<'a> HitTestVisitor<'a> {
+    /// Hit tests `node` itself, and returns the result, the origin and layer of its children and
+    /// the area that they are clipped to.
+    fn hit<'b, T, M, N>(
+        &self,
+        node: &N,
+    ) -> (Hit, Option<layout::Point>, u8, Option<layout::Geometry>)
+    where
+        T: theme::Theme<'b>,
+        N: widget::Widget<'b, T, M>,
+    {
+        let layer = self.layer.max(node.layer());
+        // Widgets in higher layers, such as popups, are not clipped by their ancestors
+        let clip = if layer == self.layer { self.clip } else { None };
+        let state = node.layout_state();
+        let (id, geometry) = match (state.id(), self.layout.get_geometry(state)) {
+            (Some(id), Some(geometry)) => (id, geometry.translate(self.offset)),
+            _ => return (Hit::default(), None, layer, clip),
+        };
+
+        let visible = clip.map_or(true, |clip| clip.contains(self.point));
+        let widget = if visible && geometry.contains(self.point) {
+            Some((layer, id))
+        } else {
+            None
+        };
+        let modal = if node.is_modal() {
+            Some((layer, id))
+        } else {
+            None
+        };
+        let child_clip = if node.clips_children() {
+            Some(clip.map_or(geometry, |clip| geometry.intersect(clip)))
+        } else {
+            clip
+        };
+        let origin = geometry.child_origin(node.scroll_offset());
+        (Hit { widget, modal }, Some(origin), layer, child_clip)
+    }
+}
+
+impl Hit {
+    /// Combines this hit with that of a widget that is drawn after it.
+    fn then(self, other: Hit) -> Hit {
+        let later = |a: Option<(u8, widget::Id)>, b: Option<(u8, widget::Id)>| match (a, b) {
+            (Some((a_layer, _)), Some((b_layer, _))) if a_layer > b_layer => a,
+            (a, None) => a,
+            (_, b) => b,
+        };
+        Hit {
+            widget: later(self.widget, other.widget),
+            modal: later(self.modal, other.modal),
+        }
+    }
+}
+
+impl<'a, 'b, T, M> widget::Visitor<'b, T, M> for HitTestVisitor<'a>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Hit;
+    type ChildVisitor = HitTestChildVisitor<'a>;
+
+    fn accept_leaf<N>(self, node: &N) -> Self::Output
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        self.hit(node).0
+    }
+
+    fn accept_node<N>(self, node: &N, _child_count: usize) -> Self::ChildVisitor
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        let (hit, offset, layer, clip) = self.hit(node);
+        let layout = self.layout;
+        let point = self.point;
+
+        HitTestChildVisitor {
+            layout,
+            offset,
+            point,
+            layer,
+            clip,
+            hit,
+        }
+    }
+}
+
+impl<'a, 'b, T, M> widget::ChildVisitor<'b, T, M> for HitTestChildVisitor<'a>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Hit;
+
+    fn accept_child<W>(&mut self, widget: &W)
+    where
+        W: widget::Node<'b, T, M>,
+    {
+        // Children are considered even if the parent wasn't hit, since popups in higher layers
+        // may extend beyond their parent; only widgets that have never been laid out are skipped
+        if let Some(offset) = self.offset {
+            let layout = self.layout;
+            let point = self.point;
+            let layer = self.layer;
+            let clip = self.clip;
+            let hit = widget.visit(HitTestVisitor {
+                layout,
+                offset,
+                point,
+                layer,
+                clip,
+            });
+            self.hit = self.hit.then(hit);
+        }
+    }
+
+    fn end(self) -> Self::Output {
+        self.hit
+    }
+}
+
+impl<'b, T, M> widget::Visitor<'b, T, M> for DepthVisitor
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Option<usize>;
+    type ChildVisitor = DepthChildVisitor;
+
+    fn accept_leaf<N>(self, node: &N) -> Self::Output
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        Some(self.depth).filter(|_| node.layout_state().id() == Some(self.target))
+    }
+
+    fn accept_node<N>(self, node: &N, _child_count: usize) -> Self::ChildVisitor
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        let found = Some(self.depth).filter(|_| node.layout_state().id() == Some(self.target));
+        DepthChildVisitor {
+            target: self.target,
+            depth: self.depth + 1,
+            found,
+        }
+    }
+}
+
+impl<'b, T, M> widget::ChildVisitor<'b, T, M> for DepthChildVisitor
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Option<usize>;
+
+    fn accept_child<W>(&mut self, widget: &W)
+    where
+        W: widget::Node<'b, T, M>,
+    {
+        if self.found.is_none() {
+            self.found = widget.visit(DepthVisitor {
+                target: self.target,
+                depth: self.depth,
+            });
+        }
+    }
+
+    fn end(self) -> Self::Output {
+        self.found
+    }
+}
+
+impl<'a, 'b, T, M> widget::Visitor<'b, T, M> for PathVisitor<'a>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = bool;
+    type ChildVisitor = PathChildVisitor<'a>;
+
+    fn accept_leaf<N>(self, node: &N) -> Self::Output
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        node.layout_state().id() == Some(self.target)
+            && (self.depth < self.skip || self.path.push(self.target).is_ok())
+    }
+
+    fn accept_node<N>(self, node: &N, _child_count: usize) -> Self::ChildVisitor
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        let id = node.layout_state().id();
+        let entered = match id {
+            Some(_) if self.depth < self.skip => true,
+            Some(id) => self.path.push(id).is_ok(),
+            None => false,
+        };
+        let found = entered && id == Some(self.target);
+
+        PathChildVisitor {
+            target: self.target,
+            path: self.path,
+            depth: self.depth,
+            skip: self.skip,
+            entered,
+            found,
+        }
+    }
+}
+
+impl<'a, 'b, T, M> widget::ChildVisitor<'b, T, M> for PathChildVisitor<'a>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = bool;
+
+    fn accept_child<W>(&mut self, widget: &W)
+    where
+        W: widget::Node<'b, T, M>,
+    {
+        if self.entered && !self.found {
+            self.found = widget.visit(PathVisitor {
+                target: self.target,
+                path: &mut *self.path,
+                depth: self.depth + 1,
+                skip: self.skip,
+            });
+        }
+    }
+
+    fn end(self) -> Self::Output {
+        if self.entered && !self.found && self.depth >= self.skip {
+            self.path.pop();
+        }
+        self.found
+    }
+}
+
+impl<'a, T, M> Delivery<'a, T, M> {
+    /// Lets `node` handle the event in `phase`, returning whether it did.
+    fn handle<'b, N>(
+        &mut self,
+        node: &mut N,
+        geometry: layout::Geometry,
+        phase: event::Phase,
+    ) -> bool
+    where
+        T: theme::Theme<'b>,
+        N: widget::Widget<'b, T, M>,
+    {
+        let mut context = widget::EventContext {
+            theme: self.theme,
+            position: geometry.embedded_position(),
+            size: geometry.embedded_size(),
+            phase,
+            now: self.now,
+            messages: &mut *self.messages,
+            events: &mut *self.events,
+            layout: self.layout,
+        };
+        node.handle_event(&mut context, self.event)
+    }
+}
+
+impl<'v, 'a, T, M> DispatchVisitor<'v, 'a, T, M> {
+    /// Delivers the event to `node` if it is on the path, and returns the outcome so far, whether
+    /// the target may be among its children and their origin.
+    fn deliver<'b, N>(&mut self, node: &mut N) -> (Outcome, bool, layout::Point)
+    where
+        T: theme::Theme<'b>,
+        N: widget::Widget<'b, T, M>,
+    {
+        let state = node.layout_state();
+        let (id, geometry) = match (state.id(), self.delivery.layout.get_geometry(state)) {
+            (Some(id), Some(geometry)) => (id, geometry.translate(self.offset)),
+            _ => return (Outcome::default(), false, self.offset),
+        };
+        let origin = geometry.child_origin(node.scroll_offset());
+
+        if self.path.get(self.index) != Some(&id) {
+            // Above a path that is missing its outermost widgets, the target is searched for
+            return (Outcome::default(), self.index == 0, origin);
+        }
+
+        let is_target = self.index + 1 == self.path.len();
+        let phase = if is_target {
+            event::Phase::Target
+        } else {
+            event::Phase::Capture
+        };
+        let handled = self.delivery.handle(node, geometry, phase);
+        let outcome = Outcome {
+            found: handled || is_target,
+            handler: Some(id).filter(|_| handled),
+            ancestor: !is_target,
+        };
+        (outcome, !outcome.found, origin)
+    }
+}
+
+impl<'v, 'a, 'b, T, M> widget::MutVisitor<'b, T, M> for DispatchVisitor<'v, 'a, T, M>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Outcome;
+    type MutChildVisitor = DispatchChildVisitor<'v, 'a, T, M>;
+
+    fn accept_leaf_mut<N>(mut self, node: &mut N) -> Self::Output
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        self.deliver(node).0
+    }
+
+    fn accept_node_mut<N>(mut self, node: &mut N, _child_count: usize) -> Self::MutChildVisitor
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        let (outcome, search, offset) = self.deliver(node);
+        // Once on the path, only the next widget on it is looked for
+        let index = if outcome.ancestor {
+            self.index + 1
+        } else {
+            self.index
+        };
+
+        DispatchChildVisitor {
+            delivery: self.delivery,
+            path: self.path,
+            index,
+            offset,
+            search,
+            outcome,
+        }
+    }
+}
+
+impl<'v, 'a, 'b, T, M> widget::MutChildVisitor<'b, T, M> for DispatchChildVisitor<'v, 'a, T, M>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Outcome;
+
+    fn accept_child_mut<W>(&mut self, widget: &mut W)
+    where
+        W: widget::Node<'b, T, M>,
+    {
+        if !self.search || self.outcome.found {
+            return;
+        }
+
+        let outcome = widget.visit_mut(DispatchVisitor {
+            delivery: &mut *self.delivery,
+            path: self.path,
+            index: self.index,
+            offset: self.offset,
+        });
+        if outcome.found {
+            self.outcome.found = true;
+            self.outcome.handler = if outcome.ancestor && outcome.handler.is_none() {
+                // Now that everything below the child has had its turn
+                widget.visit_mut(BubbleVisitor {
+                    delivery: &mut *self.delivery,
+                    offset: self.offset,
+                })
+            } else {
+                outcome.handler
+            };
+        }
+    }
+
+    fn end(self) -> Self::Output {
+        self.outcome
+    }
+}
+
+impl<'v, 'a, T, M> BubbleVisitor<'v, 'a, T, M> {
+    fn deliver<'b, N>(&mut self, node: &mut N) -> Option<widget::Id>
+    where
+        T: theme::Theme<'b>,
+        N: widget::Widget<'b, T, M>,
+    {
+        let state = node.layout_state();
+        let (id, geometry) = match (state.id(), self.delivery.layout.get_geometry(state)) {
+            (Some(id), Some(geometry)) => (id, geometry.translate(self.offset)),
+            _ => return None,
+        };
+        Some(id).filter(|_| self.delivery.handle(node, geometry, event::Phase::Bubble))
+    }
+}
+
+impl<'v, 'a, 'b, T, M> widget::MutVisitor<'b, T, M> for BubbleVisitor<'v, 'a, T, M>
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Option<widget::Id>;
+    type MutChildVisitor = BubbleChildVisitor;
+
+    fn accept_leaf_mut<N>(mut self, node: &mut N) -> Self::Output
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        self.deliver(node)
+    }
+
+    fn accept_node_mut<N>(mut self, node: &mut N, _child_count: usize) -> Self::MutChildVisitor
+    where
+        N: widget::Widget<'b, T, M>,
+    {
+        BubbleChildVisitor {
+            handler: self.deliver(node),
+        }
+    }
+}
+
+impl<'b, T, M> widget::MutChildVisitor<'b, T, M> for BubbleChildVisitor
+where
+    T: theme::Theme<'b>,
+{
+    type Output = Option<widget::Id>;
+
+    fn accept_child_mut<W>(&mut self, _widget: &mut W)
+    where
+        W: widget::Node<'b, T, M>,
+    {
+    }
+
+    fn end(self) -> Self::Output {
+        self.handler
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, point, Probe};
+
+    fn nest<X>(child: X) -> Probe<(X,)> {
+        Probe::new('n', 0.0, 0.0, 100.0, 100.0).children((child,))
+    }
+
+    /// A target `t` in a parent `a` next to a sibling `b`, in a root `r`.
+    type Family = Probe<(Probe<(Probe<testing::Leaf>,)>, Probe<testing::Leaf>)>;
+
+    fn family(parent: Option<event::Phase>, target: Option<event::Phase>) -> Family {
+        let mut a = Probe::new('a', 0.0, 0.0, 50.0, 50.0);
+        let mut t = Probe::new('t', 10.0, 10.0, 10.0, 10.0);
+        if let Some(phase) = parent {
+            a = a.handles(phase);
+        }
+        if let Some(phase) = target {
+            t = t.handles(phase);
+        }
+        Probe::new('r', 0.0, 0.0, 100.0, 100.0)
+            .children((a.children((t,)), Probe::new('b', 50.0, 50.0, 50.0, 50.0)))
+    }
+
+    /// Dispatches an event to `t` in `tree`, returning the handler and what the probes saw.
+    fn dispatch_to_target(
+        tree: &mut Family,
+    ) -> (Option<widget::Id>, message::Queue<testing::Message>) {
+        let layout = testing::layout(tree);
+        let target = tree.children.0.children.0.id();
+        let mut messages = message::Queue::new();
+        let mut events = event::Queue::new();
+        let event = event::Event::Navigation(event::Navigation::Activate);
+        let handler = dispatch(
+            &layout,
+            &testing::theme(),
+            tree,
+            target,
+            &event,
+            time::Duration::default(),
+            &mut messages,
+            &mut events,
+        );
+        (handler, messages)
+    }
+
+    #[test]
+    fn hit_test_finds_the_deepest_and_topmost_widget() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 50.0, 50.0)
+                .children((Probe::new('c', 10.0, 10.0, 10.0, 10.0),)),
+            Probe::new('b', 40.0, 40.0, 20.0, 20.0),
+        ));
+        let layout = testing::layout(&mut tree);
+
+        let c = tree.children.0.children.0.id();
+        assert_eq!(hit_test(&layout, &tree, point(15, 15)), Some(c));
+        // Later siblings are drawn on top of earlier ones
+        assert_eq!(
+            hit_test(&layout, &tree, point(45, 45)),
+            Some(tree.children.1.id())
+        );
+        assert_eq!(hit_test(&layout, &tree, point(90, 90)), Some(tree.id()));
+        assert_eq!(hit_test(&layout, &tree, point(150, 50)), None);
+    }
+
+    #[test]
+    fn hit_test_prefers_higher_layers() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 50.0, 50.0)
+                .children((Probe::new('p', 0.0, 0.0, 80.0, 80.0).layer(1),)),
+            Probe::new('b', 40.0, 40.0, 20.0, 20.0),
+        ));
+        let layout = testing::layout(&mut tree);
+
+        let popup = tree.children.0.children.0.id();
+        assert_eq!(hit_test(&layout, &tree, point(45, 45)), Some(popup));
+        // Also outside of the parent of the popup
+        assert_eq!(hit_test(&layout, &tree, point(70, 70)), Some(popup));
+        assert_eq!(hit_test(&layout, &tree, point(90, 90)), Some(tree.id()));
+    }
+
+    #[test]
+    fn hit_test_is_blocked_by_modal_widgets() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 50.0, 50.0),
+            Probe::new('o', 20.0, 20.0, 30.0, 30.0)
+                .layer(2)
+                .modal()
+                .children((Probe::new('d', 5.0, 5.0, 10.0, 10.0),)),
+        ));
+        let layout = testing::layout(&mut tree);
+
+        let overlay = tree.children.1.id();
+        let inner = tree.children.1.children.0.id();
+        assert_eq!(modal(&layout, &tree), Some(overlay));
+        assert_eq!(hit_test(&layout, &tree, point(5, 5)), Some(overlay));
+        assert_eq!(hit_test(&layout, &tree, point(90, 90)), Some(overlay));
+        assert_eq!(hit_test(&layout, &tree, point(30, 30)), Some(inner));
+        assert_eq!(hit_test(&layout, &tree, point(45, 45)), Some(overlay));
+    }
+
+    #[test]
+    fn hit_test_is_clipped() {
+        let scroll = Probe::new('s', 0.0, 0.0, 50.0, 50.0).clip().children((
+            Probe::new('c', 30.0, 30.0, 40.0, 40.0),
+            Probe::new('p', 40.0, 40.0, 30.0, 30.0).layer(1),
+        ));
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((scroll,));
+        let layout = testing::layout(&mut tree);
+
+        let scroll = &tree.children.0;
+        assert_eq!(modal(&layout, &tree), None);
+        assert_eq!(
+            hit_test(&layout, &tree, point(35, 35)),
+            Some(scroll.children.0.id())
+        );
+        // Widgets in higher layers are not clipped by their ancestors
+        assert_eq!(
+            hit_test(&layout, &tree, point(65, 65)),
+            Some(scroll.children.1.id())
+        );
+        assert_eq!(hit_test(&layout, &tree, point(60, 35)), Some(tree.id()));
+    }
+
+    #[test]
+    fn path_leads_to_the_target() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 50.0, 50.0),
+            Probe::new('b', 50.0, 50.0, 50.0, 50.0)
+                .children((Probe::new('c', 0.0, 0.0, 10.0, 10.0),)),
+        ));
+        testing::layout(&mut tree);
+
+        let b = &tree.children.1;
+        let c = b.children.0.id();
+        let expected = [tree.id(), b.id(), c];
+        assert_eq!(&path(&tree, c).unwrap()[..], &expected[..]);
+        assert_eq!(&path(&tree, tree.id()).unwrap()[..], &expected[..1]);
+    }
+
+    #[test]
+    fn path_is_limited_to_max_depth() {
+        let tree = Probe::new('t', 0.0, 0.0, 100.0, 100.0).handles(event::Phase::Target);
+        let tree = nest(nest(nest(nest(tree))));
+        let tree = nest(nest(nest(nest(tree))));
+        let tree = nest(nest(nest(nest(tree))));
+        let mut tree = nest(nest(nest(nest(nest(tree)))));
+        let layout = testing::layout(&mut tree);
+
+        let target = hit_test(&layout, &tree, point(50, 50)).unwrap();
+        let path = path(&tree, target).unwrap();
+        assert_eq!(path.len(), path.capacity());
+        assert_eq!(path.last(), Some(&target));
+        assert!(!path.contains(&tree.id()));
+
+        // The outermost ancestors are left out, but the target is still reached
+        let mut messages = message::Queue::new();
+        let mut events = event::Queue::new();
+        let event = event::Event::Navigation(event::Navigation::Activate);
+        let handler = dispatch(
+            &layout,
+            &testing::theme(),
+            &mut tree,
+            target,
+            &event,
+            time::Duration::default(),
+            &mut messages,
+            &mut events,
+        );
+        assert_eq!(handler, Some(target));
+    }
+
+    #[test]
+    fn dispatch_captures_targets_and_bubbles() {
+        let mut tree = family(None, None);
+        let (handler, messages) = dispatch_to_target(&mut tree);
+        assert_eq!(handler, None);
+        assert_eq!(
+            &messages[..],
+            &[
+                ('r', event::Phase::Capture),
+                ('a', event::Phase::Capture),
+                ('t', event::Phase::Target),
+                ('a', event::Phase::Bubble),
+                ('r', event::Phase::Bubble),
+            ][..]
+        );
+    }
+
+    #[test]
+    fn dispatch_stops_when_handled() {
+        let mut tree = family(Some(event::Phase::Capture), None);
+        let (handler, messages) = dispatch_to_target(&mut tree);
+        assert_eq!(handler, Some(tree.children.0.id()));
+        assert_eq!(
+            &messages[..],
+            &[('r', event::Phase::Capture), ('a', event::Phase::Capture)][..]
+        );
+
+        let mut tree = family(None, Some(event::Phase::Target));
+        let (handler, messages) = dispatch_to_target(&mut tree);
+        assert_eq!(handler, Some(tree.children.0.children.0.id()));
+        assert_eq!(
+            &messages[..],
+            &[
+                ('r', event::Phase::Capture),
+                ('a', event::Phase::Capture),
+                ('t', event::Phase::Target),
+            ][..]
+        );
+
+        let mut tree = family(Some(event::Phase::Bubble), None);
+        let (handler, messages) = dispatch_to_target(&mut tree);
+        assert_eq!(handler, Some(tree.children.0.id()));
+        assert_eq!(
+            &messages[..],
+            &[
+                ('r', event::Phase::Capture),
+                ('a', event::Phase::Capture),
+                ('t', event::Phase::Target),
+                ('a', event::Phase::Bubble),
+            ][..]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Calibration of touch panels, mapping raw touch controller readings to display coordinates.
+//!
+//! The mapping is an affine transformation, which covers scaling, offsets, rotation, mirroring and
+//! small amounts of skew.  It is usually computed using three-point calibration, where the user
+//! touches three known points on the screen; `widgets::calibration::Calibration` is a screen that
+//! does this.
+use crate::event;
+use crate::input;
+use core::time;
+
+/// An affine transformation of touch coordinates.
+///
+/// A point `(x, y)` is transformed to `(a * x + b * y + c, d * x + e * y + f)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
+}
+
+/// A rotation of the touch panel relative to the display, in clockwise steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+/// An input source that applies a calibration matrix to the pointer events of another source.
+#[derive(Debug)]
+pub struct Calibrated<S> {
+    source: S,
+    matrix: Matrix,
+}
+
+impl Matrix {
+    pub fn identity() -> Self {
+        Self::from_coefficients([1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+    }
+
+    /// Creates a matrix from the coefficients `[a, b, c, d, e, f]`, e.g. after loading it from
+    /// persistent storage.
+    pub fn from_coefficients(coefficients: [f32; 6]) -> Self {
+        let [a, b, c, d, e, f] = coefficients;
+        Self { a, b, c, d, e, f }
+    }
+
+    /// The coefficients `[a, b, c, d, e, f]` of the matrix, e.g. for persisting it.
+    pub fn coefficients(&self) -> [f32; 6] {
+        [self.a, self.b, self.c, self.d, self.e, self.f]
+    }
+
+    /// Computes the matrix that maps each of the `raw` points to the corresponding `display` point.
+    ///
+    /// Returns `None` if the raw points are on a line, so that no such matrix exists.
+    pub fn from_points(
+        raw: [embedded_graphics::geometry::Point; 3],
+        display: [embedded_graphics::geometry::Point; 3],
+    ) -> Option<Self> {
+        let [(x1, y1), (x2, y2), (x3, y3)] = [to_f32(raw[0]), to_f32(raw[1]), to_f32(raw[2])];
+        let [(u1, v1), (u2, v2), (u3, v3)] =
+            [to_f32(display[0]), to_f32(display[1]), to_f32(display[2])];
+
+        let det = (x1 - x3) * (y2 - y3) - (x2 - x3) * (y1 - y3);
+        if det.abs() < core::f32::EPSILON {
+            return None;
+        }
+
+        let a = ((u1 - u3) * (y2 - y3) - (u2 - u3) * (y1 - y3)) / det;
+        let b = ((x1 - x3) * (u2 - u3) - (x2 - x3) * (u1 - u3)) / det;
+        let c = u3 - a * x3 - b * y3;
+        let d = ((v1 - v3) * (y2 - y3) - (v2 - v3) * (y1 - y3)) / det;
+        let e = ((x1 - x3) * (v2 - v3) - (x2 - x3) * (v1 - v3)) / det;
+        let f = v3 - d * x3 - e * y3;
+
+        Some(Self { a, b, c, d, e, f })
+    }
+
+    /// Scales coordinates in the range `0..from` to the range `0..to`, e.g. to map the full range
+    /// of a touch controller onto the display before it has been calibrated.
+    pub fn scale(
+        from: embedded_graphics::geometry::Size,
+        to: embedded_graphics::geometry::Size,
+    ) -> Self {
+        let a = to.width as f32 / from.width.max(1) as f32;
+        let e = to.height as f32 / from.height.max(1) as f32;
+        Self::from_coefficients([a, 0.0, 0.0, 0.0, e, 0.0])
+    }
+
+    /// Rotates coordinates within an area of the specified size.
+    ///
+    /// For quarter turns, the width and height of the resulting area are swapped.
+    pub fn rotation(rotation: Rotation, size: embedded_graphics::geometry::Size) -> Self {
+        let w = size.width as f32 - 1.0;
+        let h = size.height as f32 - 1.0;
+        let coefficients = match rotation {
+            Rotation::Deg0 => [1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+            Rotation::Deg90 => [0.0, -1.0, h, 1.0, 0.0, 0.0],
+            Rotation::Deg180 => [-1.0, 0.0, w, 0.0, -1.0, h],
+            Rotation::Deg270 => [0.0, 1.0, 0.0, -1.0, 0.0, w],
+        };
+        Self::from_coefficients(coefficients)
+    }
+
+    /// Mirrors coordinates horizontally within an area of the specified width.
+    pub fn mirror_x(width: u32) -> Self {
+        Self::from_coefficients([-1.0, 0.0, width as f32 - 1.0, 0.0, 1.0, 0.0])
+    }
+
+    /// Mirrors coordinates vertically within an area of the specified height.
+    pub fn mirror_y(height: u32) -> Self {
+        Self::from_coefficients([1.0, 0.0, 0.0, 0.0, -1.0, height as f32 - 1.0])
+    }
+
+    /// Returns the matrix that first applies `self`, and then `next`.
+    pub fn then(&self, next: &Matrix) -> Matrix {
+        Matrix {
+            a: next.a * self.a + next.b * self.d,
+            b: next.a * self.b + next.b * self.e,
+            c: next.a * self.c + next.b * self.f + next.c,
+            d: next.d * self.a + next.e * self.d,
+            e: next.d * self.b + next.e * self.e,
+            f: next.d * self.c + next.e * self.f + next.f,
+        }
+    }
+
+    pub fn transform(
+        &self,
+        point: embedded_graphics::geometry::Point,
+    ) -> embedded_graphics::geometry::Point {
+        let (x, y) = to_f32(point);
+        let tx = self.a * x + self.b * y + self.c;
+        let ty = self.d * x + self.e * y + self.f;
+        embedded_graphics::geometry::Point::new(round(tx), round(ty))
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<S> Calibrated<S>
+where
+    S: input::Source,
+{
+    pub fn new(source: S, matrix: Matrix) -> Self {
+        Self { source, matrix }
+    }
+
+    pub fn matrix(&self) -> Matrix {
+        self.matrix
+    }
+
+    pub fn set_matrix(&mut self, matrix: Matrix) {
+        self.matrix = matrix;
+    }
+
+    pub fn into_source(self) -> S {
+        self.source
+    }
+}
+
+impl<S> input::Source for Calibrated<S>
+where
+    S: input::Source,
+{
+    fn poll(&mut self, now: time::Duration) -> Option<event::Event> {
+        match self.source.poll(now)? {
+            event::Event::Pointer(pointer) => {
+                let position = self.matrix.transform(pointer.position);
+                Some(event::Pointer::new(pointer.action, position).into())
+            }
+            event::Event::Hover(position) => {
+                Some(event::Event::Hover(self.matrix.transform(position)))
+            }
+            event => Some(event),
+        }
+    }
+}
+
+fn to_f32(point: embedded_graphics::geometry::Point) -> (f32, f32) {
+    (point.x as f32, point.y as f32)
+}
+
+fn round(value: f32) -> i32 {
+    if value < 0.0 {
+        (value - 0.5) as i32
+    } else {
+        (value + 0.5) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: i32, y: i32) -> embedded_graphics::geometry::Point {
+        embedded_graphics::geometry::Point::new(x, y)
+    }
+
+    fn size(width: u32, height: u32) -> embedded_graphics::geometry::Size {
+        embedded_graphics::geometry::Size::new(width, height)
+    }
+
+    #[test]
+    fn from_points_round_trip() {
+        // A 12 bit touch controller mounted upside down over a 320x240 display
+        let raw = [point(3800, 3600), point(300, 2000), point(2000, 400)];
+        let display = [point(32, 24), point(288, 120), point(160, 216)];
+        let matrix = Matrix::from_points(raw, display).unwrap();
+        for (&raw, &display) in raw.iter().zip(display.iter()) {
+            assert_eq!(matrix.transform(raw), display);
+        }
+
+        // Points other than the calibration points are mapped consistently
+        let center = matrix.transform(point(2050, 2000));
+        assert!((center.x - 160).abs() <= 1 && (center.y - 120).abs() <= 1);
+    }
+
+    #[test]
+    fn from_points_recovers_matrix() {
+        let expected = Matrix::from_coefficients([0.5, 0.1, -20.0, -0.2, 0.75, 10.0]);
+        let raw = [point(100, 100), point(900, 200), point(400, 800)];
+        let display = [
+            expected.transform(raw[0]),
+            expected.transform(raw[1]),
+            expected.transform(raw[2]),
+        ];
+        let matrix = Matrix::from_points(raw, display).unwrap();
+        for (actual, expected) in matrix.coefficients().iter().zip(&expected.coefficients()) {
+            assert!((actual - expected).abs() < 0.01, "{:?}", matrix);
+        }
+    }
+
+    #[test]
+    fn from_points_degenerate() {
+        let display = [point(0, 0), point(100, 0), point(0, 100)];
+        let collinear = [point(0, 0), point(10, 10), point(20, 20)];
+        assert_eq!(Matrix::from_points(collinear, display), None);
+        let repeated = [point(5, 5), point(5, 5), point(50, 10)];
+        assert_eq!(Matrix::from_points(repeated, display), None);
+    }
+
+    #[test]
+    fn then_applies_in_order() {
+        let scale = Matrix::scale(size(100, 100), size(200, 100));
+        let mirror = Matrix::mirror_x(200);
+        let matrix = scale.then(&mirror);
+        let p = point(10, 20);
+        assert_eq!(matrix.transform(p), mirror.transform(scale.transform(p)));
+        assert_eq!(matrix.transform(p), point(179, 20));
+        assert_ne!(matrix, mirror.then(&scale));
+    }
+
+    #[test]
+    fn rotations_compose() {
+        let area = size(320, 240);
+        let corner = point(319, 0);
+
+        let quarter = Matrix::rotation(Rotation::Deg90, area);
+        assert_eq!(quarter.transform(corner), point(239, 319));
+
+        // A quarter turn of the swapped area turns the rest of the way around
+        let swapped = size(240, 320);
+        let half = quarter.then(&Matrix::rotation(Rotation::Deg90, swapped));
+        let expected = Matrix::rotation(Rotation::Deg180, area);
+        assert_eq!(half.transform(corner), expected.transform(corner));
+        assert_eq!(half.transform(corner), point(0, 239));
+
+        let back = quarter.then(&Matrix::rotation(Rotation::Deg270, swapped));
+        assert_eq!(back.transform(corner), corner);
+        assert_eq!(back.transform(point(12, 34)), point(12, 34));
+    }
+
+    /// A source that returns the given events in order.
+    struct Events<'a>(&'a [event::Event]);
+
+    impl<'a> input::Source for Events<'a> {
+        fn poll(&mut self, _now: time::Duration) -> Option<event::Event> {
+            let (&first, rest) = self.0.split_first()?;
+            self.0 = rest;
+            Some(first)
+        }
+    }
+
+    #[test]
+    fn calibrated_positions() {
+        use crate::input::Source;
+
+        let events = [
+            event::Event::Hover(point(10, 20)),
+            event::Pointer::new(event::PointerAction::Down, point(10, 20)).into(),
+            event::Navigation::Next.into(),
+        ];
+        let mirror = Matrix::mirror_x(200);
+        let mut source = Calibrated::new(Events(&events), mirror);
+        let now = time::Duration::default();
+
+        assert_eq!(source.poll(now), Some(event::Event::Hover(point(189, 20))));
+        assert_eq!(
+            source.poll(now),
+            Some(event::Pointer::new(event::PointerAction::Down, point(189, 20)).into())
+        );
+        assert_eq!(source.poll(now), Some(event::Navigation::Next.into()));
+        assert_eq!(source.poll(now), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Quadrature rotary encoders.
+use crate::event;
+use crate::input;
+use core::time;
+
+/// A quadrature rotary encoder connected to two `embedded-hal` input pins.
+///
+/// Turning the encoder clockwise moves the focus to the next widget and turning it counter-clockwise
+/// moves it to the previous one; if it's the other way around, swap the pins.  A push button built
+/// into the encoder can be added as a separate `gpio::Button` emitting
+/// `event::Navigation::Activate`.  The encoder itself never goes back, so a device that should e.g.
+/// leave submenus with a button needs another `gpio::Button` emitting `event::Navigation::Back`;
+/// without one, menus are left through the row at the top of every submenu.
+///
+/// Each transition of the pins has to be observed for the rotation to be decoded, so if the encoder
+/// is not polled often enough, `sample` should additionally be called from e.g. a timer interrupt.
+#[derive(Debug)]
+pub struct RotaryEncoder<A, B> {
+    pin_a: A,
+    pin_b: B,
+    steps_per_detent: i8,
+    state: u8,
+    steps: i8,
+    detents: i32,
+}
+
+// Indexed by `previous_state << 2 | state`, where a state is `a << 1 | b`
+const TRANSITIONS: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+impl<A, B> RotaryEncoder<A, B>
+where
+    A: embedded_hal::digital::v2::InputPin,
+    B: embedded_hal::digital::v2::InputPin,
+{
+    pub fn new(pin_a: A, pin_b: B) -> Self {
+        let mut encoder = Self {
+            pin_a,
+            pin_b,
+            steps_per_detent: 4,
+            state: 0,
+            steps: 0,
+            detents: 0,
+        };
+        encoder.state = encoder.read();
+        encoder
+    }
+
+    /// Sets how many quadrature steps there are per detent (click) of the encoder; defaults to 4.
+    pub fn steps_per_detent(mut self, steps_per_detent: i8) -> Self {
+        self.steps_per_detent = steps_per_detent.max(1);
+        self
+    }
+
+    /// Samples the pins and decodes any rotation since the last sample.
+    pub fn sample(&mut self) {
+        let state = self.read();
+        self.steps += TRANSITIONS[usize::from(self.state << 2 | state)];
+        self.state = state;
+
+        if self.steps >= self.steps_per_detent {
+            self.steps = 0;
+            self.detents += 1;
+        } else if self.steps <= -self.steps_per_detent {
+            self.steps = 0;
+            self.detents -= 1;
+        }
+    }
+
+    pub fn into_pins(self) -> (A, B) {
+        (self.pin_a, self.pin_b)
+    }
+
+    fn read(&self) -> u8 {
+        // A pin that can't be read is treated as low
+        let a = self.pin_a.is_high().unwrap_or(false);
+        let b = self.pin_b.is_high().unwrap_or(false);
+        (a as u8) << 1 | b as u8
+    }
+}
+
+impl<A, B> input::Source for RotaryEncoder<A, B>
+where
+    A: embedded_hal::digital::v2::InputPin,
+    B: embedded_hal::digital::v2::InputPin,
+{
+    fn poll(&mut self, _now: time::Duration) -> Option<event::Event> {
+        self.sample();
+
+        if self.detents > 0 {
+            self.detents -= 1;
+            Some(event::Navigation::Next.into())
+        } else if self.detents < 0 {
+            self.detents += 1;
+            Some(event::Navigation::Previous.into())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Source;
+    use core::cell;
+
+    /// A pin whose level is set by the test.
+    struct Pin<'a>(&'a cell::Cell<bool>);
+
+    impl<'a> embedded_hal::digital::v2::InputPin for Pin<'a> {
+        type Error = ();
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.0.get())
+        }
+    }
+
+    /// The levels of the pins over one detent clockwise, as `(a, b)`.
+    const CLOCKWISE: [(bool, bool); 4] =
+        [(true, false), (true, true), (false, true), (false, false)];
+
+    #[test]
+    fn transitions_are_symmetric() {
+        // Going from one state to another and back is no rotation
+        for previous in 0..4 {
+            for state in 0..4 {
+                let forward = TRANSITIONS[previous << 2 | state];
+                let backward = TRANSITIONS[state << 2 | previous];
+                assert_eq!(forward, -backward);
+            }
+        }
+    }
+
+    #[test]
+    fn detents() {
+        let a = cell::Cell::new(false);
+        let b = cell::Cell::new(false);
+        let mut encoder = RotaryEncoder::new(Pin(&a), Pin(&b));
+        let now = time::Duration::default();
+
+        let mut turn = |steps: &mut dyn Iterator<Item = &(bool, bool)>| {
+            let mut events = (0, 0);
+            for &(level_a, level_b) in steps {
+                a.set(level_a);
+                b.set(level_b);
+                match encoder.poll(now) {
+                    Some(event::Event::Navigation(event::Navigation::Next)) => events.0 += 1,
+                    Some(event::Event::Navigation(event::Navigation::Previous)) => events.1 += 1,
+                    None => {}
+                    Some(event) => panic!("unexpected event {:?}", event),
+                }
+            }
+            events
+        };
+
+        assert_eq!(turn(&mut CLOCKWISE.iter()), (1, 0));
+        assert_eq!(turn(&mut CLOCKWISE.iter().chain(CLOCKWISE.iter())), (2, 0));
+        // Counter-clockwise ends up back where the last clockwise detent started
+        assert_eq!(turn(&mut CLOCKWISE.iter().rev().skip(1)), (0, 0));
+        assert_eq!(turn(&mut CLOCKWISE.iter().rev().take(1)), (0, 1));
+
+        // Half a detent and back is no rotation
+        assert_eq!(turn(&mut CLOCKWISE.iter().take(2)), (0, 0));
+        assert_eq!(turn(&mut [(true, false), (false, false)].iter()), (0, 0));
+        assert_eq!(turn(&mut CLOCKWISE.iter()), (1, 0));
+    }
+}
@@ -0,0 +1,203 @@
+//! Buttons connected to digital input pins.
+use crate::event;
+use crate::input;
+use core::time;
+
+/// A button connected to an `embedded-hal` input pin, which emits a navigation event when pressed.
+///
+/// The pin is sampled every time the button is polled.  Its state has to be stable for the
+/// debounce time before a press or release is registered, and while the button is held down the
+/// event can optionally be repeated.
+#[derive(Debug)]
+pub struct Button<P> {
+    pin: P,
+    navigation: event::Navigation,
+    active_low: bool,
+    debounce: time::Duration,
+    repeat: Option<Repeat>,
+    raw: bool,
+    raw_since: time::Duration,
+    pressed: bool,
+    next_repeat: Option<time::Duration>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Repeat {
+    delay: time::Duration,
+    interval: time::Duration,
+}
+
+impl<P> Button<P>
+where
+    P: embedded_hal::digital::v2::InputPin,
+{
+    /// Creates a button that emits `navigation` and that is pressed when the pin is high.
+    pub fn new(pin: P, navigation: event::Navigation) -> Self {
+        Self {
+            pin,
+            navigation,
+            active_low: false,
+            debounce: time::Duration::from_millis(20),
+            repeat: None,
+            raw: false,
+            raw_since: time::Duration::default(),
+            pressed: false,
+            next_repeat: None,
+        }
+    }
+
+    /// Makes the button count as pressed when the pin is low, e.g. when using a pull-up resistor.
+    pub fn active_low(mut self) -> Self {
+        self.active_low = true;
+        self
+    }
+
+    /// Sets how long the pin must be stable before a change is registered; defaults to 20 ms.
+    pub fn debounce(mut self, debounce: time::Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Repeats the event every `interval` once the button has been held down for `delay`.
+    pub fn auto_repeat(mut self, delay: time::Duration, interval: time::Duration) -> Self {
+        self.repeat = Some(Repeat { delay, interval });
+        self
+    }
+
+    /// Whether the button is currently pressed, after debouncing.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    pub fn into_pin(self) -> P {
+        self.pin
+    }
+
+    fn sample(&self) -> bool {
+        // A pin that can't be read is treated as released
+        match self.pin.is_high() {
+            Ok(high) => high != self.active_low,
+            Err(_) => false,
+        }
+    }
+}
+
+impl<P> input::Source for Button<P>
+where
+    P: embedded_hal::digital::v2::InputPin,
+{
+    fn poll(&mut self, now: time::Duration) -> Option<event::Event> {
+        let raw = self.sample();
+        if raw != self.raw {
+            self.raw = raw;
+            self.raw_since = now;
+        }
+
+        let stable = now.checked_sub(self.raw_since).unwrap_or_default() >= self.debounce;
+        if stable && self.raw != self.pressed {
+            self.pressed = self.raw;
+            self.next_repeat = if self.pressed {
+                self.repeat.map(|repeat| now + repeat.delay)
+            } else {
+                None
+            };
+
+            if self.pressed {
+                return Some(self.navigation.into());
+            }
+        }
+
+        match (self.next_repeat, self.repeat) {
+            (Some(next_repeat), Some(repeat)) if self.pressed && now >= next_repeat => {
+                self.next_repeat = Some(now + repeat.interval);
+                Some(self.navigation.into())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Source;
+    use core::cell;
+
+    /// A pin whose level is set by the test.
+    struct Pin<'a>(&'a cell::Cell<bool>);
+
+    impl<'a> embedded_hal::digital::v2::InputPin for Pin<'a> {
+        type Error = ();
+
+        fn is_high(&self) -> Result<bool, Self::Error> {
+            Ok(self.0.get())
+        }
+
+        fn is_low(&self) -> Result<bool, Self::Error> {
+            Ok(!self.0.get())
+        }
+    }
+
+    fn ms(millis: u64) -> time::Duration {
+        time::Duration::from_millis(millis)
+    }
+
+    fn next() -> Option<event::Event> {
+        Some(event::Navigation::Next.into())
+    }
+
+    #[test]
+    fn press_is_debounced() {
+        let level = cell::Cell::new(false);
+        let mut button = Button::new(Pin(&level), event::Navigation::Next);
+        assert_eq!(button.poll(ms(0)), None);
+
+        // Bouncing contacts restart the debounce time
+        level.set(true);
+        assert_eq!(button.poll(ms(10)), None);
+        level.set(false);
+        assert_eq!(button.poll(ms(15)), None);
+        level.set(true);
+        assert_eq!(button.poll(ms(20)), None);
+        assert_eq!(button.poll(ms(35)), None);
+        assert!(!button.is_pressed());
+
+        assert_eq!(button.poll(ms(40)), next());
+        assert!(button.is_pressed());
+        assert_eq!(button.poll(ms(50)), None);
+
+        // Releasing emits nothing
+        level.set(false);
+        assert_eq!(button.poll(ms(60)), None);
+        assert_eq!(button.poll(ms(80)), None);
+        assert!(!button.is_pressed());
+    }
+
+    #[test]
+    fn active_low() {
+        let level = cell::Cell::new(true);
+        let mut button = Button::new(Pin(&level), event::Navigation::Next).active_low();
+        assert_eq!(button.poll(ms(0)), None);
+        level.set(false);
+        assert_eq!(button.poll(ms(10)), None);
+        assert_eq!(button.poll(ms(30)), next());
+    }
+
+    #[test]
+    fn auto_repeat() {
+        let level = cell::Cell::new(false);
+        let mut button =
+            Button::new(Pin(&level), event::Navigation::Next).auto_repeat(ms(300), ms(100));
+        level.set(true);
+        assert_eq!(button.poll(ms(0)), None);
+        assert_eq!(button.poll(ms(20)), next());
+        assert_eq!(button.poll(ms(200)), None);
+        assert_eq!(button.poll(ms(320)), next());
+        assert_eq!(button.poll(ms(400)), None);
+        assert_eq!(button.poll(ms(420)), next());
+
+        level.set(false);
+        assert_eq!(button.poll(ms(450)), None);
+        assert_eq!(button.poll(ms(600)), None);
+    }
+}
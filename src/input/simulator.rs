@@ -0,0 +1,116 @@
+//! Input from the `embedded-graphics-simulator` window.
+//!
+//! Mouse buttons act as a touch panel, and moving the mouse while no button is pressed hovers over
+//! widgets.  The arrow keys move the focus spatially, tab and shift+tab move it in tree order,
+//! return activates the focused widget and escape goes back.  Printable characters, backspace,
+//! delete, home and end edit text, and shift selects text while moving the cursor.  Space is a
+//! printable character too, so unlike return, it does not activate widgets.
+use crate::event;
+use crate::input;
+use core::time;
+use embedded_graphics_simulator::sdl2::Keycode;
+use embedded_graphics_simulator::sdl2::Mod;
+use embedded_graphics_simulator::SimulatorEvent;
+
+/// An input source that translates simulator window events.
+#[derive(Debug)]
+pub struct Events<I> {
+    events: I,
+    mouse_down: bool,
+}
+
+impl<I> Events<I>
+where
+    I: Iterator<Item = SimulatorEvent>,
+{
+    pub fn new(events: I) -> Self {
+        let mouse_down = false;
+        Self { events, mouse_down }
+    }
+}
+
+impl<I> input::Source for Events<I>
+where
+    I: Iterator<Item = SimulatorEvent>,
+{
+    fn poll(&mut self, _now: time::Duration) -> Option<event::Event> {
+        let mouse_down = &mut self.mouse_down;
+        self.events
+            .by_ref()
+            .find_map(|window_event| translate(window_event, mouse_down))
+    }
+}
+
+fn translate(window_event: SimulatorEvent, mouse_down: &mut bool) -> Option<event::Event> {
+    match window_event {
+        SimulatorEvent::MouseButtonDown { point, .. } => {
+            *mouse_down = true;
+            Some(event::Pointer::new(event::PointerAction::Down, point).into())
+        }
+        SimulatorEvent::MouseButtonUp { point, .. } => {
+            *mouse_down = false;
+            Some(event::Pointer::new(event::PointerAction::Up, point).into())
+        }
+        // Like a touch panel, the mouse is only dragged while a button is held, but it also hovers
+        SimulatorEvent::MouseMove { point } if *mouse_down => {
+            Some(event::Pointer::new(event::PointerAction::Move, point).into())
+        }
+        SimulatorEvent::MouseMove { point } => Some(event::Event::Hover(point)),
+        SimulatorEvent::KeyDown {
+            keycode, keymod, ..
+        } => edit(keycode, keymod)
+            .map(Into::into)
+            .or_else(|| navigation(keycode, keymod).map(Into::into)),
+        _ => None,
+    }
+}
+
+fn navigation(keycode: Keycode, keymod: Mod) -> Option<event::Navigation> {
+    match keycode {
+        Keycode::Tab if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) => {
+            Some(event::Navigation::Previous)
+        }
+        Keycode::Tab => Some(event::Navigation::Next),
+        Keycode::Return => Some(event::Navigation::Activate),
+        Keycode::Escape => Some(event::Navigation::Back),
+        Keycode::Up => Some(event::Navigation::Up),
+        Keycode::Down => Some(event::Navigation::Down),
+        Keycode::Left => Some(event::Navigation::Left),
+        Keycode::Right => Some(event::Navigation::Right),
+        _ => None,
+    }
+}
+
+fn edit(keycode: Keycode, keymod: Mod) -> Option<event::Edit> {
+    let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+    let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+    let motion = |motion| {
+        Some(event::Edit::Move {
+            motion,
+            select: shift,
+        })
+    };
+
+    match keycode {
+        Keycode::Backspace => Some(event::Edit::Backspace),
+        Keycode::Delete => Some(event::Edit::Delete),
+        Keycode::Home => motion(event::Motion::Home),
+        Keycode::End => motion(event::Motion::End),
+        // Without shift, the arrow keys are left to navigation
+        Keycode::Left if shift => motion(event::Motion::Left),
+        Keycode::Right if shift => motion(event::Motion::Right),
+        Keycode::A if ctrl => Some(event::Edit::SelectAll),
+        _ if ctrl => None,
+        keycode => {
+            // The keycodes of printable keys are the ASCII codes of their unshifted characters
+            let code = keycode as i32;
+            if code >= 0x20 && code < 0x7f {
+                let c = code as u8;
+                let c = if shift { c.to_ascii_uppercase() } else { c };
+                Some(event::Edit::Insert(char::from(c)))
+            } else {
+                None
+            }
+        }
+    }
+}
@@ -15,7 +15,7 @@ pub struct Geometry {
     pub size: Size,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -68,10 +68,10 @@ impl Layout {
         })
     }
 
-    pub fn update_tree<'a, T, N>(&mut self, width: u32, height: u32, theme: &T, widget: &mut N)
+    pub fn update_tree<'a, T, M, N>(&mut self, width: u32, height: u32, theme: &T, widget: &mut N)
     where
         T: theme::Theme<'a>,
-        N: widget::Node<'a, T>,
+        N: widget::Node<'a, T, M>,
     {
         let stretch = &mut self.stretch;
         let root = widget.visit_mut(StretchVisitor { stretch, theme });
@@ -89,13 +89,80 @@ impl fmt::Debug for Layout {
     }
 }
 
+impl Geometry {
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.position.x
+            && point.y >= self.position.y
+            && point.x < self.position.x + self.size.width
+            && point.y < self.position.y + self.size.height
+    }
+
+    /// The part of this geometry that is also within `other`.
+    pub(crate) fn intersect(self, other: Geometry) -> Self {
+        let x1 = self.position.x.max(other.position.x);
+        let y1 = self.position.y.max(other.position.y);
+        let x2 = (self.position.x + self.size.width).min(other.position.x + other.size.width);
+        let y2 = (self.position.y + self.size.height).min(other.position.y + other.size.height);
+        let position = Point { x: x1, y: y1 };
+        let size = Size {
+            width: (x2 - x1).max(0.0),
+            height: (y2 - y1).max(0.0),
+        };
+        Geometry { position, size }
+    }
+
+    /// The point that the children of a widget with this geometry are positioned relative to, if
+    /// the widget scrolls them by `scroll`.
+    pub(crate) fn child_origin(&self, scroll: Point) -> Point {
+        Point {
+            x: self.position.x - scroll.x,
+            y: self.position.y - scroll.y,
+        }
+    }
+
+    pub(crate) fn translate(self, offset: Point) -> Self {
+        let position = Point {
+            x: offset.x + self.position.x,
+            y: offset.y + self.position.y,
+        };
+        let size = self.size;
+        Geometry { position, size }
+    }
+
+    pub(crate) fn embedded_position(&self) -> embedded_graphics::geometry::Point {
+        embedded_graphics::geometry::Point::new(self.position.x as i32, self.position.y as i32)
+    }
+
+    pub(crate) fn embedded_size(&self) -> embedded_graphics::geometry::Size {
+        embedded_graphics::geometry::Size::new(
+            self.size.width.max(0.0) as u32,
+            self.size.height.max(0.0) as u32,
+        )
+    }
+}
+
+impl From<embedded_graphics::geometry::Point> for Point {
+    fn from(point: embedded_graphics::geometry::Point) -> Self {
+        let x = point.x as f32;
+        let y = point.y as f32;
+        Point { x, y }
+    }
+}
+
 impl State {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// The identity of the widget owning this state, which stays the same across frames.
+    ///
+    /// Returns `None` if the widget has never been laid out.
+    pub fn id(&self) -> Option<widget::Id> {
+        self.node.map(widget::Id)
+    }
 }
 
-impl<'a, 'b, T> widget::MutVisitor<'b, T> for StretchVisitor<'a, T>
+impl<'a, 'b, T, M> widget::MutVisitor<'b, T, M> for StretchVisitor<'a, T>
 where
     T: theme::Theme<'b>,
 {
@@ -104,7 +171,7 @@ where
 
     fn accept_leaf_mut<W>(self, widget: &mut W) -> Self::Output
     where
-        W: widget::Widget<'b, T>,
+        W: widget::Widget<'b, T, M>,
     {
         let style = widget.layout_style(self.theme);
         let node = *widget
@@ -121,7 +188,7 @@ where
 
     fn accept_node_mut<W>(self, widget: &mut W, child_count: usize) -> Self::MutChildVisitor
     where
-        W: widget::Widget<'b, T>,
+        W: widget::Widget<'b, T, M>,
     {
         let style = widget.layout_style(self.theme);
 
@@ -151,7 +218,7 @@ where
     }
 }
 
-impl<'a, 'b, T> widget::MutChildVisitor<'b, T> for StretchChildVisitor<'a, T>
+impl<'a, 'b, T, M> widget::MutChildVisitor<'b, T, M> for StretchChildVisitor<'a, T>
 where
     T: theme::Theme<'b>,
 {
@@ -159,7 +226,7 @@ where
 
     fn accept_child_mut<N>(&mut self, node: &mut N)
     where
-        N: widget::Node<'b, T>,
+        N: widget::Node<'b, T, M>,
     {
         let stretch = &mut self.stretch;
         let theme = self.theme;
@@ -1,10 +1,20 @@
 #![no_std]
 
 use core::marker;
+use core::time;
 
+pub mod animation;
+pub mod app;
 pub mod children;
+pub mod event;
+pub mod focus;
+pub mod gesture;
+pub mod input;
 pub mod layout;
+pub mod message;
 pub mod renderer;
+#[cfg(test)]
+mod testing;
 pub mod theme;
 pub mod util;
 pub mod widget;
@@ -18,6 +28,9 @@ where
 {
     layout: layout::Layout,
     drawing: D,
+    focus: Option<widget::Id>,
+    hover: Option<widget::Id>,
+    capture: Option<widget::Id>,
     phantom: marker::PhantomData<C>,
 }
 
@@ -28,22 +41,171 @@ where
 {
     pub fn new(drawing: D) -> Self {
         let layout = layout::Layout::new();
+        let focus = None;
+        let hover = None;
+        let capture = None;
         let phantom = marker::PhantomData;
         Self {
             layout,
             drawing,
+            focus,
+            hover,
+            capture,
             phantom,
         }
     }
 
-    pub fn update<'a, T, W>(&'a mut self, width: u32, height: u32, theme: &'a T, widget: &mut W)
-    where
+    /// Lays out the widget tree to fit the given size, advances its animations to `now` and draws
+    /// it; see `animation`.
+    pub fn update<'a, T, M, W>(
+        &mut self,
+        width: u32,
+        height: u32,
+        theme: &T,
+        widget: &mut W,
+        now: time::Duration,
+    ) where
         T: theme::Theme<'a, Color = C>,
         T::Font: embedded_graphics::fonts::Font<'a, C>,
-        W: widget::Node<'a, T>,
+        W: widget::Node<'a, T, M>,
     {
         self.layout.update_tree(width, height, theme, widget);
-        renderer::Renderer::new(theme, &self.layout, &mut self.drawing).render_tree(widget);
+        let focus = self.focus;
+        animation::animate(&self.layout, widget, now, focus);
+        let hover = self.hover;
+        renderer::Renderer::new(theme, &self.layout, &mut self.drawing, focus, hover)
+            .render_tree(widget);
+    }
+
+    /// Delivers an input event to the widget tree that was most recently passed to `update`.
+    ///
+    /// Pointer events go to the deepest widget under the pointer, according to the layout computed
+    /// during the last call to `update`, and gestures go to the widget under the point where they
+    /// started.  The widget that handles a pointer press captures the pointer, so it receives all
+    /// further pointer events until the pointer is released, even if it moves elsewhere, and also
+    /// receives the focus if it is focusable.  Hover events only change which widget is hovered.
+    ///
+    /// Navigation and editing events go to the focused widget, and navigation events move the focus
+    /// if that widget does not handle them.  While a modal widget such as an open dialog is shown,
+    /// a focus behind it is first moved into it; see `focus::confine`.  The ancestors of the target
+    /// get to intercept the event before and after it; see `input::dispatch`.  Events that the
+    /// widgets post while handling the event are delivered the same way afterwards.  `now` is the
+    /// time at which the event happened, as returned by the application clock.  Returns the
+    /// messages emitted by the widgets while handling the event.
+    pub fn handle_event<'a, T, M, W>(
+        &mut self,
+        theme: &T,
+        widget: &mut W,
+        event: event::Event,
+        now: time::Duration,
+    ) -> message::Queue<M>
+    where
+        T: theme::Theme<'a, Color = C>,
+        W: widget::Node<'a, T, M>,
+    {
+        let mut messages = message::Queue::new();
+        let mut events = event::Queue::new();
+        self.deliver(theme, widget, event, now, &mut messages, &mut events);
+
+        // Posted events may post further events, but only until the queue is full
+        let mut index = 0;
+        while let Some(&event) = events.get(index) {
+            index += 1;
+            let mut posted = event::Queue::new();
+            self.deliver(theme, widget, event, now, &mut messages, &mut posted);
+            for &event in posted.iter() {
+                let _ = events.push(event);
+            }
+        }
+
+        messages
+    }
+
+    fn deliver<'a, T, M, W>(
+        &mut self,
+        theme: &T,
+        widget: &mut W,
+        event: event::Event,
+        now: time::Duration,
+        messages: &mut message::Queue<M>,
+        events: &mut event::Queue,
+    ) where
+        T: theme::Theme<'a, Color = C>,
+        W: widget::Node<'a, T, M>,
+    {
+        let target = match event {
+            event::Event::Hover(position) => {
+                self.hover = input::hit_test(&self.layout, widget, position);
+                return;
+            }
+            event::Event::Pointer(pointer) => {
+                let target = input::hit_test(&self.layout, widget, pointer.position);
+                self.hover = match pointer.action {
+                    event::PointerAction::Down | event::PointerAction::Move => target,
+                    event::PointerAction::Up => None,
+                };
+                match pointer.action {
+                    event::PointerAction::Down => target,
+                    _ => self.capture.or(target),
+                }
+            }
+            event::Event::Navigation(_) | event::Event::Edit(_) => {
+                self.focus = focus::confine(&self.layout, widget, self.focus);
+                self.focus
+            }
+            event::Event::Gesture(gesture) => {
+                input::hit_test(&self.layout, widget, gesture.position())
+            }
+        };
+
+        let handler = if let Some(target) = target {
+            let layout = &self.layout;
+            input::dispatch(layout, theme, widget, target, &event, now, messages, events)
+        } else {
+            None
+        };
+
+        match event {
+            event::Event::Pointer(pointer) => {
+                self.capture = match pointer.action {
+                    event::PointerAction::Down => handler,
+                    event::PointerAction::Move => self.capture,
+                    event::PointerAction::Up => None,
+                };
+                if pointer.action == event::PointerAction::Down && handler.is_some() {
+                    let mut focusable = false;
+                    focus::for_each_focusable(&self.layout, widget, |id, _| {
+                        focusable |= Some(id) == handler;
+                    });
+                    if focusable {
+                        self.focus = handler;
+                    }
+                }
+            }
+            event::Event::Navigation(navigation) if handler.is_none() => {
+                self.focus = focus::find(&self.layout, widget, self.focus, navigation);
+            }
+            _ => {}
+        }
+    }
+
+    /// The widget that currently receives navigation events, if any.
+    pub fn focus(&self) -> Option<widget::Id> {
+        self.focus
+    }
+
+    pub fn set_focus(&mut self, focus: Option<widget::Id>) {
+        self.focus = focus;
+    }
+
+    /// The widget that is currently under the pointer, if any.
+    pub fn hover(&self) -> Option<widget::Id> {
+        self.hover
+    }
+
+    /// The widget that has captured the pointer during a press, if any.
+    pub fn pointer_capture(&self) -> Option<widget::Id> {
+        self.capture
     }
 
     pub fn drawing(&self) -> &D {
@@ -58,3 +220,110 @@ where
         self.drawing
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, Probe};
+
+    /// An instance that has laid out `widget`, as if it had been drawn.
+    fn emui<W>(widget: &mut W) -> Emui<testing::Color, testing::Display>
+    where
+        W: widget::Node<'static, testing::Theme<'static>, testing::Message>,
+    {
+        let mut emui = Emui::new(testing::Display);
+        emui.layout.update_tree(100, 100, &testing::theme(), widget);
+        emui
+    }
+
+    fn handle<W>(
+        emui: &mut Emui<testing::Color, testing::Display>,
+        widget: &mut W,
+        event: event::Event,
+    ) -> message::Queue<testing::Message>
+    where
+        W: widget::Node<'static, testing::Theme<'static>, testing::Message>,
+    {
+        emui.handle_event(&testing::theme(), widget, event, time::Duration::default())
+    }
+
+    fn pointer(action: event::PointerAction, x: i32, y: i32) -> event::Event {
+        let position = embedded_graphics::geometry::Point::new(x, y);
+        event::Pointer::new(action, position).into()
+    }
+
+    #[test]
+    fn unhandled_navigation_moves_the_focus() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 50.0, 50.0).focusable(),
+            Probe::new('b', 50.0, 0.0, 50.0, 50.0).focusable(),
+        ));
+        let mut emui = emui(&mut tree);
+
+        emui.set_focus(Some(tree.children.0.id()));
+        let messages = handle(&mut emui, &mut tree, event::Navigation::Next.into());
+        assert_eq!(
+            &messages[..],
+            &[
+                ('r', event::Phase::Capture),
+                ('a', event::Phase::Target),
+                ('r', event::Phase::Bubble),
+            ][..]
+        );
+        assert_eq!(emui.focus(), Some(tree.children.1.id()));
+    }
+
+    #[test]
+    fn handled_navigation_keeps_the_focus() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0)
+            .handles(event::Phase::Capture)
+            .children((
+                Probe::new('a', 0.0, 0.0, 50.0, 50.0).focusable(),
+                Probe::new('b', 50.0, 0.0, 50.0, 50.0).focusable(),
+            ));
+        let mut emui = emui(&mut tree);
+
+        emui.set_focus(Some(tree.children.0.id()));
+        let messages = handle(&mut emui, &mut tree, event::Navigation::Next.into());
+        // The root intercepts the event before it reaches the focused widget
+        assert_eq!(&messages[..], &[('r', event::Phase::Capture)][..]);
+        assert_eq!(emui.focus(), Some(tree.children.0.id()));
+    }
+
+    #[test]
+    fn pointer_is_captured_by_the_pressed_widget() {
+        let mut tree = Probe::new('r', 0.0, 0.0, 100.0, 100.0).children((
+            Probe::new('a', 0.0, 0.0, 50.0, 50.0)
+                .focusable()
+                .handles(event::Phase::Target),
+            Probe::new('b', 50.0, 50.0, 50.0, 50.0).handles(event::Phase::Target),
+        ));
+        let mut emui = emui(&mut tree);
+        let a = tree.children.0.id();
+        let (press, drag, release) = (
+            event::PointerAction::Down,
+            event::PointerAction::Move,
+            event::PointerAction::Up,
+        );
+        let pressed = [('r', event::Phase::Capture), ('a', event::Phase::Target)];
+
+        let messages = handle(&mut emui, &mut tree, pointer(press, 10, 10));
+        assert_eq!(&messages[..], &pressed[..]);
+        assert_eq!(emui.pointer_capture(), Some(a));
+        assert_eq!(emui.focus(), Some(a));
+
+        // Even once the pointer has moved over another widget
+        let messages = handle(&mut emui, &mut tree, pointer(drag, 70, 70));
+        assert_eq!(&messages[..], &pressed[..]);
+        assert_eq!(emui.hover(), Some(tree.children.1.id()));
+        let messages = handle(&mut emui, &mut tree, pointer(release, 70, 70));
+        assert_eq!(&messages[..], &pressed[..]);
+        assert_eq!(emui.pointer_capture(), None);
+
+        let messages = handle(&mut emui, &mut tree, pointer(drag, 70, 70));
+        assert_eq!(
+            &messages[..],
+            &[('r', event::Phase::Capture), ('b', event::Phase::Target)][..]
+        );
+    }
+}
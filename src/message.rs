@@ -0,0 +1,7 @@
+//! Messages emitted by widgets to tell the application about user interactions.
+
+/// The capacity of a message queue, i.e. how many messages can be emitted per event.
+pub type Capacity = heapless::consts::U8;
+
+/// A fixed-capacity queue of messages, so that no allocator is needed.
+pub type Queue<M> = heapless::Vec<M, Capacity>;
@@ -6,75 +6,141 @@ pub struct Renderer<'a, T, D> {
     theme: &'a T,
     layout: &'a layout::Layout,
     drawing: &'a mut D,
+    focus: Option<widget::Id>,
+    hover: Option<widget::Id>,
+    layer: u8,
+    top_layer: u8,
 }
 
-impl<'a, T, D> Renderer<'a, T, D>
-where
-    T: theme::Theme<'a>,
-    D: embedded_graphics::Drawing<T::Color>,
-{
-    pub fn new(theme: &'a T, layout: &'a layout::Layout, drawing: &'a mut D) -> Self {
+impl<'a, T, D> Renderer<'a, T, D> {
+    pub fn new(
+        theme: &'a T,
+        layout: &'a layout::Layout,
+        drawing: &'a mut D,
+        focus: Option<widget::Id>,
+        hover: Option<widget::Id>,
+    ) -> Self {
+        let layer = 0;
+        let top_layer = 0;
         Self {
             theme,
             layout,
             drawing,
+            focus,
+            hover,
+            layer,
+            top_layer,
         }
     }
 
-    pub fn render_tree<W>(self, widget: &W)
+    /// Draws the widget tree in tree order, one layer at a time; see `widget::Widget::layer`.
+    pub fn render_tree<'b, M, W>(self, widget: &W)
     where
-        W: widget::Node<'a, T>,
+        T: theme::Theme<'b>,
+        D: embedded_graphics::Drawing<T::Color>,
+        W: widget::Node<'b, T, M>,
     {
-        let renderer = self;
+        let mut renderer = self;
         let offset = layout::Point { x: 0.0, y: 0.0 };
-        widget.visit(RendererVisitor { offset, renderer });
+        let layer = 0;
+        let clip = None;
+        loop {
+            renderer = widget.visit(RendererVisitor {
+                offset,
+                layer,
+                clip,
+                renderer,
+            });
+            if renderer.layer >= renderer.top_layer {
+                break;
+            }
+            renderer.layer += 1;
+        }
     }
 
-    fn render<N>(&mut self, offset: layout::Point, node: &N) -> layout::Point
+    /// Draws the parts of `node` within `clip` if it is in the layer being drawn, and returns the
+    /// origin and layer of its children and the area that they are clipped to.
+    fn render<'b, M, N>(
+        &mut self,
+        offset: layout::Point,
+        layer: u8,
+        clip: Option<layout::Geometry>,
+        node: &N,
+    ) -> (layout::Point, u8, Option<layout::Geometry>)
     where
-        N: widget::Widget<'a, T>,
+        T: theme::Theme<'b>,
+        D: embedded_graphics::Drawing<T::Color>,
+        N: widget::Widget<'b, T, M>,
     {
-        let geometry = self.layout.get_geometry(node.layout_state()).unwrap();
+        let state = node.layout_state();
+        let geometry = self.layout.get_geometry(state).unwrap().translate(offset);
+        let parent_layer = layer;
+        let layer = layer.max(node.layer());
+        // Widgets in higher layers, such as popups, are not clipped by their ancestors
+        let clip = if layer == parent_layer { clip } else { None };
+        let child_clip = if node.clips_children() {
+            Some(clip.map_or(geometry, |clip| geometry.intersect(clip)))
+        } else {
+            clip
+        };
+        let origin = geometry.child_origin(node.scroll_offset());
+        self.top_layer = self.top_layer.max(layer);
+        if layer != self.layer {
+            return (origin, layer, child_clip);
+        }
+
         let theme = self.theme;
-        let position = embedded_graphics::geometry::Point::new(
-            (offset.x + geometry.position.x) as i32,
-            (offset.y + geometry.position.y) as i32,
-        );
-        let size = embedded_graphics::geometry::Size::new(
-            geometry.size.width.max(0.0) as u32,
-            geometry.size.height.max(0.0) as u32,
-        );
+        let position = geometry.embedded_position();
+        let size = geometry.embedded_size();
+        let focused = self.focus.is_some() && state.id() == self.focus;
+        let hovered = self.hover.is_some() && state.id() == self.hover;
         let draw_context = widget::DrawContext {
             theme,
             position,
             size,
+            focused,
+            hovered,
+            layout: self.layout,
+        };
+        let visible = move |pixel: &embedded_graphics::drawable::Pixel<T::Color>| {
+            clip.map_or(true, |clip| clip.contains(pixel.0.into()))
         };
-        self.drawing.draw(node.draw(&draw_context));
-        geometry.position
+        self.drawing.draw(node.draw(&draw_context).filter(visible));
+
+        if focused {
+            let x1 = position.x - 1;
+            let y1 = position.y - 1;
+            let x2 = position.x + size.width as i32;
+            let y2 = position.y + size.height as i32;
+            let ring = embedded_graphics::egrectangle!(
+                (x1, y1),
+                (x2, y2),
+                stroke = Some(theme.focus_color())
+            );
+            self.drawing.draw(ring.into_iter().filter(visible));
+        }
+
+        (origin, layer, child_clip)
     }
 }
 
-struct RendererVisitor<'a, T, D>
-where
-    T: theme::Theme<'a>,
-    D: embedded_graphics::Drawing<T::Color>,
-{
+struct RendererVisitor<'a, T, D> {
     offset: layout::Point,
+    layer: u8,
+    clip: Option<layout::Geometry>,
     renderer: Renderer<'a, T, D>,
 }
 
-struct RendererChildVisitor<'a, T, D>
-where
-    T: theme::Theme<'a>,
-    D: embedded_graphics::Drawing<T::Color>,
-{
+struct RendererChildVisitor<'a, T, D> {
     offset: layout::Point,
+    layer: u8,
+    clip: Option<layout::Geometry>,
     renderer: Option<Renderer<'a, T, D>>,
 }
 
-impl<'a, T, D> widget::Visitor<'a, T> for RendererVisitor<'a, T, D>
+impl<'a, 'b, T, D, M> widget::Visitor<'b, T, M> for RendererVisitor<'a, T, D>
 where
-    T: theme::Theme<'a>,
+    T: theme::Theme<'b>,
     D: embedded_graphics::Drawing<T::Color>,
 {
     type Output = Renderer<'a, T, D>;
@@ -82,37 +148,52 @@ where
 
     fn accept_leaf<N>(mut self, node: &N) -> Self::Output
     where
-        N: widget::Widget<'a, T>,
+        N: widget::Widget<'b, T, M>,
     {
-        self.renderer.render(self.offset, node);
+        self.renderer
+            .render(self.offset, self.layer, self.clip, node);
         self.renderer
     }
 
     fn accept_node<N>(mut self, node: &N, _child_count: usize) -> Self::ChildVisitor
     where
-        N: widget::Widget<'a, T>,
+        N: widget::Widget<'b, T, M>,
     {
-        let offset = self.renderer.render(self.offset, node);
+        let (offset, layer, clip) = self
+            .renderer
+            .render(self.offset, self.layer, self.clip, node);
 
         let renderer = Some(self.renderer);
-        RendererChildVisitor { offset, renderer }
+        RendererChildVisitor {
+            offset,
+            layer,
+            clip,
+            renderer,
+        }
     }
 }
 
-impl<'a, T, D> widget::ChildVisitor<'a, T> for RendererChildVisitor<'a, T, D>
+impl<'a, 'b, T, D, M> widget::ChildVisitor<'b, T, M> for RendererChildVisitor<'a, T, D>
 where
-    T: theme::Theme<'a>,
+    T: theme::Theme<'b>,
     D: embedded_graphics::Drawing<T::Color>,
 {
     type Output = Renderer<'a, T, D>;
 
     fn accept_child<W>(&mut self, widget: &W)
     where
-        W: widget::Node<'a, T>,
+        W: widget::Node<'b, T, M>,
     {
         let offset = self.offset;
+        let layer = self.layer;
+        let clip = self.clip;
         let renderer = self.renderer.take().unwrap();
-        self.renderer = Some(widget.visit(RendererVisitor { offset, renderer }));
+        self.renderer = Some(widget.visit(RendererVisitor {
+            offset,
+            layer,
+            clip,
+            renderer,
+        }));
     }
 
     fn end(mut self) -> Self::Output {
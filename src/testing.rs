@@ -0,0 +1,264 @@
+//! Widgets and themes for testing how events and focus move through a widget tree.
+use crate::children;
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::widget;
+use core::marker;
+
+pub(crate) type Color = embedded_graphics::pixelcolor::BinaryColor;
+
+pub(crate) type Theme<'a> = theme::SimpleTheme<Color, embedded_graphics::fonts::Font6x8<'a, Color>>;
+
+/// Identifies the probe that handled an event, and in which phase.
+pub(crate) type Message = (char, event::Phase);
+
+/// A widget at a fixed position within its parent, that reports every event it sees by emitting a
+/// `Message`.
+#[derive(Debug)]
+pub(crate) struct Probe<X> {
+    pub layout: layout::State,
+    pub children: X,
+    name: char,
+    geometry: layout::Geometry,
+    layer: u8,
+    modal: bool,
+    clip: bool,
+    focusable: bool,
+    handles: Option<event::Phase>,
+}
+
+/// The children of a probe without children.
+#[derive(Debug)]
+pub(crate) struct Leaf;
+
+/// A display that throws away everything drawn on it.
+#[derive(Debug)]
+pub(crate) struct Display;
+
+pub(crate) fn theme<'a>() -> Theme<'a> {
+    theme::SimpleTheme {
+        spacing: 2.0,
+        background_color: Color::Off,
+        text_color: Color::On,
+        border_color: Color::On,
+        fill_color: Color::On,
+        stripe_color: Color::Off,
+        focus_color: Color::On,
+        hover_color: Color::Off,
+        pressed_color: Color::On,
+        disabled_color: Color::On,
+        phantom: marker::PhantomData,
+    }
+}
+
+/// Lays out `widget` on a 100x100 display.
+pub(crate) fn layout<N>(widget: &mut N) -> layout::Layout
+where
+    N: widget::Node<'static, Theme<'static>, Message>,
+{
+    let mut layout = layout::Layout::new();
+    layout.update_tree(100, 100, &theme(), widget);
+    layout
+}
+
+pub(crate) fn point(x: i32, y: i32) -> embedded_graphics::geometry::Point {
+    embedded_graphics::geometry::Point::new(x, y)
+}
+
+impl Probe<Leaf> {
+    pub fn new(name: char, x: f32, y: f32, width: f32, height: f32) -> Self {
+        let layout = layout::State::new();
+        let children = Leaf;
+        let position = layout::Point { x, y };
+        let size = layout::Size { width, height };
+        let geometry = layout::Geometry { position, size };
+        Self {
+            layout,
+            children,
+            name,
+            geometry,
+            layer: 0,
+            modal: false,
+            clip: false,
+            focusable: false,
+            handles: None,
+        }
+    }
+}
+
+impl<X> Probe<X> {
+    pub fn children<Y>(self, children: Y) -> Probe<Y> {
+        let Probe {
+            layout,
+            name,
+            geometry,
+            layer,
+            modal,
+            clip,
+            focusable,
+            handles,
+            ..
+        } = self;
+        Probe {
+            layout,
+            children,
+            name,
+            geometry,
+            layer,
+            modal,
+            clip,
+            focusable,
+            handles,
+        }
+    }
+
+    pub fn layer(mut self, layer: u8) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn modal(mut self) -> Self {
+        self.modal = true;
+        self
+    }
+
+    pub fn clip(mut self) -> Self {
+        self.clip = true;
+        self
+    }
+
+    pub fn focusable(mut self) -> Self {
+        self.focusable = true;
+        self
+    }
+
+    /// Makes the probe handle events in `phase`, which stops their delivery.
+    pub fn handles(mut self, phase: event::Phase) -> Self {
+        self.handles = Some(phase);
+        self
+    }
+
+    /// The identity of the probe; it must have been laid out.
+    pub fn id(&self) -> widget::Id {
+        self.layout.id().unwrap()
+    }
+}
+
+impl<X> widget::Node<'static, Theme<'static>, Message> for Probe<X>
+where
+    X: children::Children<'static, Theme<'static>, Message>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'static, Theme<'static>, Message>,
+    {
+        if self.children.is_empty() {
+            visitor.accept_leaf(self)
+        } else {
+            let visitor = visitor.accept_node(self, self.children.len());
+            self.children.visit_children(visitor)
+        }
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'static, Theme<'static>, Message>,
+    {
+        if self.children.is_empty() {
+            visitor.accept_leaf_mut(self)
+        } else {
+            let visitor = visitor.accept_node_mut(self, self.children.len());
+            self.children.visit_children_mut(visitor)
+        }
+    }
+}
+
+impl<X> widget::Widget<'static, Theme<'static>, Message> for Probe<X> {
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<Theme<'static>>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, _theme: &Theme<'static>) -> layout::Style {
+        let layout::Geometry { position, size } = self.geometry;
+        // Without the margins of the base style, so that the geometry is exactly as given
+        layout::Style {
+            position_type: stretch::style::PositionType::Absolute,
+            position: stretch::geometry::Rect {
+                start: stretch::style::Dimension::Points(position.x),
+                end: stretch::style::Dimension::Undefined,
+                top: stretch::style::Dimension::Points(position.y),
+                bottom: stretch::style::Dimension::Undefined,
+            },
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(size.width),
+                height: stretch::style::Dimension::Points(size.height),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.focusable
+    }
+
+    fn layer(&self) -> u8 {
+        self.layer
+    }
+
+    fn is_modal(&self) -> bool {
+        self.modal
+    }
+
+    fn clips_children(&self) -> bool {
+        self.clip
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<Theme<'static>, Message>,
+        _event: &event::Event,
+    ) -> bool {
+        let phase = context.phase;
+        context.emit((self.name, phase));
+        self.handles == Some(phase)
+    }
+}
+
+impl children::Children<'static, Theme<'static>, Message> for Leaf {
+    fn len(&self) -> usize {
+        0
+    }
+
+    fn visit_children<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::ChildVisitor<'static, Theme<'static>, Message>,
+    {
+        visitor.end()
+    }
+
+    fn visit_children_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutChildVisitor<'static, Theme<'static>, Message>,
+    {
+        visitor.end()
+    }
+}
+
+impl embedded_graphics::Drawing<Color> for Display {
+    fn draw<T>(&mut self, _item: T)
+    where
+        T: IntoIterator<Item = embedded_graphics::drawable::Pixel<Color>>,
+    {
+    }
+}
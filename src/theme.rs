@@ -27,6 +27,41 @@ pub trait Theme<'a> {
     fn border_color(&self) -> Self::Color;
 
     fn fill_color(&self) -> Self::Color;
+
+    /// The fill color of every other row of a table, to make rows easier to tell apart.
+    ///
+    /// Defaults to the background color, i.e. no stripes, as text may not be readable on top of
+    /// the fill color on monochrome displays.
+    fn stripe_color(&self) -> Self::Color {
+        self.background_color()
+    }
+
+    /// The color of the indicator drawn around the focused widget.
+    fn focus_color(&self) -> Self::Color {
+        self.border_color()
+    }
+
+    /// The fill color of an interactive widget while the pointer is over it.
+    fn hover_color(&self) -> Self::Color {
+        self.fill_color()
+    }
+
+    /// The fill color of an interactive widget while it is being pressed.
+    ///
+    /// Text on top of it is drawn in the background color.
+    fn pressed_color(&self) -> Self::Color {
+        self.border_color()
+    }
+
+    /// The color used instead of the text and border colors for disabled widgets.
+    fn disabled_color(&self) -> Self::Color {
+        self.fill_color()
+    }
+
+    /// The width of the scrollbars of scrollable containers, which are drawn in the fill color.
+    fn scrollbar_width(&self) -> f32 {
+        3.0
+    }
 }
 
 #[derive(Debug)]
@@ -36,6 +71,11 @@ pub struct SimpleTheme<C, F> {
     pub text_color: C,
     pub border_color: C,
     pub fill_color: C,
+    pub stripe_color: C,
+    pub focus_color: C,
+    pub hover_color: C,
+    pub pressed_color: C,
+    pub disabled_color: C,
     pub phantom: marker::PhantomData<F>,
 }
 
@@ -68,4 +108,24 @@ where
     fn fill_color(&self) -> Self::Color {
         self.fill_color
     }
+
+    fn stripe_color(&self) -> Self::Color {
+        self.stripe_color
+    }
+
+    fn focus_color(&self) -> Self::Color {
+        self.focus_color
+    }
+
+    fn hover_color(&self) -> Self::Color {
+        self.hover_color
+    }
+
+    fn pressed_color(&self) -> Self::Color {
+        self.pressed_color
+    }
+
+    fn disabled_color(&self) -> Self::Color {
+        self.disabled_color
+    }
 }
@@ -1,5 +1,224 @@
 use crate::theme;
+use core::ops;
 pub(crate) type FontIter<'a, T> = <<T as theme::Theme<'a>>::Font as IntoIterator>::IntoIter;
 pub(crate) type RectIter<C> =
     <embedded_graphics::primitives::Rectangle<C> as IntoIterator>::IntoIter;
 pub(crate) type OptionalIter<I> = core::iter::Flatten<core::option::IntoIter<I>>;
+pub(crate) type LineIter<C> = <embedded_graphics::primitives::Line<C> as IntoIterator>::IntoIter;
+pub(crate) type CircleIter<C> =
+    <embedded_graphics::primitives::Circle<C> as IntoIterator>::IntoIter;
+
+/// A widget that is drawn one part at a time, e.g. one cell of a table after the other, so that
+/// only the part being drawn needs to be kept.
+pub trait Parts<'a, T>
+where
+    T: theme::Theme<'a>,
+{
+    /// The capacity of the text rendered by `Part::Glyphs`.
+    type Capacity: heapless::ArrayLength<u8>;
+
+    /// Moves on to the next part, or returns `None` once all parts have been drawn.
+    fn advance(&mut self) -> Option<Part<'a, T, Self::Capacity>>;
+}
+
+/// Something drawn as a part of a widget; see `Parts`.
+pub enum Part<'a, T, N>
+where
+    T: theme::Theme<'a>,
+    N: heapless::ArrayLength<u8>,
+{
+    Rect(RectIter<T::Color>),
+    Text(FontIter<'a, T>),
+    Glyphs(Glyphs<'a, T, N>),
+}
+
+/// The pixels of all the parts of a widget, in the order they are produced by `P`.
+pub struct Pixels<'a, T, P>
+where
+    T: theme::Theme<'a>,
+    P: Parts<'a, T>,
+{
+    parts: P,
+    current: Option<Part<'a, T, P::Capacity>>,
+}
+
+/// The pixels of text that is rendered one character at a time, for text in a buffer that does
+/// not live as long as the font requires.
+///
+/// Only printable ASCII characters are rendered, and the font is assumed to be monospaced.
+pub struct Glyphs<'a, T, N>
+where
+    T: theme::Theme<'a>,
+    N: heapless::ArrayLength<u8>,
+{
+    text: heapless::String<N>,
+    range: ops::Range<usize>,
+    position: embedded_graphics::geometry::Point,
+    advance: i32,
+    color: T::Color,
+    highlight: Option<(ops::Range<usize>, T::Color)>,
+    mask: Option<u8>,
+    index: usize,
+    current: Option<FontIter<'a, T>>,
+}
+
+impl<'a, T, P> Pixels<'a, T, P>
+where
+    T: theme::Theme<'a>,
+    P: Parts<'a, T>,
+{
+    pub(crate) fn new(parts: P) -> Self {
+        let current = None;
+        Self { parts, current }
+    }
+}
+
+impl<'a, T, P> Iterator for Pixels<'a, T, P>
+where
+    T: theme::Theme<'a>,
+    P: Parts<'a, T>,
+{
+    type Item = embedded_graphics::drawable::Pixel<T::Color>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(pixel) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(pixel);
+            }
+            self.current = Some(self.parts.advance()?);
+        }
+    }
+}
+
+impl<'a, T, N> Iterator for Part<'a, T, N>
+where
+    T: theme::Theme<'a>,
+    N: heapless::ArrayLength<u8>,
+{
+    type Item = embedded_graphics::drawable::Pixel<T::Color>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Part::Rect(pixels) => pixels.next(),
+            Part::Text(pixels) => pixels.next(),
+            Part::Glyphs(pixels) => pixels.next(),
+        }
+    }
+}
+
+impl<'a, T, N> Glyphs<'a, T, N>
+where
+    T: theme::Theme<'a>,
+    N: heapless::ArrayLength<u8>,
+{
+    /// Renders `text` starting at `position`, with `advance` pixels between characters.
+    pub(crate) fn new(
+        text: heapless::String<N>,
+        position: embedded_graphics::geometry::Point,
+        advance: i32,
+        color: T::Color,
+    ) -> Self {
+        let range = 0..text.len();
+        let highlight = None;
+        let mask = None;
+        let index = 0;
+        let current = None;
+        Self {
+            text,
+            range,
+            position,
+            advance,
+            color,
+            highlight,
+            mask,
+            index,
+            current,
+        }
+    }
+
+    /// Only renders the characters in `range`, with the first of them at the starting position.
+    pub(crate) fn range(mut self, range: ops::Range<usize>) -> Self {
+        let end = range.end.min(self.text.len());
+        self.range = range.start.min(end)..end;
+        self
+    }
+
+    /// Renders the characters in `range` in a different color, e.g. for selected text.
+    pub(crate) fn highlight(mut self, range: ops::Range<usize>, color: T::Color) -> Self {
+        self.highlight = Some((range, color));
+        self
+    }
+
+    /// Renders every character as `mask`, e.g. for passwords.
+    pub(crate) fn mask(mut self, mask: u8) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+}
+
+impl<'a, T, N> Iterator for Glyphs<'a, T, N>
+where
+    T: theme::Theme<'a>,
+    N: heapless::ArrayLength<u8>,
+{
+    type Item = embedded_graphics::drawable::Pixel<T::Color>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        loop {
+            if let Some(pixel) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(pixel);
+            }
+
+            let i = self.range.start + self.index;
+            if i >= self.range.end {
+                return None;
+            }
+            let c = self.mask.unwrap_or(self.text.as_bytes()[i]);
+            let color = match &self.highlight {
+                Some((range, color)) if range.contains(&i) => *color,
+                _ => self.color,
+            };
+            let x = self.position.x + self.index as i32 * self.advance;
+            let position = embedded_graphics::geometry::Point::new(x, self.position.y);
+            self.index += 1;
+            self.current = Some(
+                T::Font::render_str(glyph(c))
+                    .translate(position)
+                    .stroke(Some(color))
+                    .into_iter(),
+            );
+        }
+    }
+}
+
+/// Appends as many of the characters of `s` to `text` as fit.
+pub(crate) fn push_truncated<N>(text: &mut heapless::String<N>, s: &str)
+where
+    N: heapless::ArrayLength<u8>,
+{
+    for c in s.chars() {
+        if text.push(c).is_err() {
+            break;
+        }
+    }
+}
+
+/// A string that lives long enough to be rendered, for a printable ASCII character.
+pub(crate) fn glyph(c: u8) -> &'static str {
+    const ASCII: &str = concat!(
+        " !\"#$%&'()*+,-./0123456789:;<=>?",
+        "@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_",
+        "`abcdefghijklmnopqrstuvwxyz{|}~"
+    );
+    match c {
+        b' '..=b'~' => {
+            let i = usize::from(c - b' ');
+            &ASCII[i..=i]
+        }
+        _ => " ",
+    }
+}
@@ -1,20 +1,23 @@
+use crate::event;
 use crate::layout;
+use crate::message;
 use crate::theme;
+use core::time;
 
-pub trait Node<'a, T>
+pub trait Node<'a, T, M>
 where
     T: theme::Theme<'a>,
 {
     fn visit<V>(&self, visitor: V) -> V::Output
     where
-        V: Visitor<'a, T>;
+        V: Visitor<'a, T, M>;
 
     fn visit_mut<V>(&mut self, visitor: V) -> V::Output
     where
-        V: MutVisitor<'a, T>;
+        V: MutVisitor<'a, T, M>;
 }
 
-pub trait Widget<'a, T>
+pub trait Widget<'a, T, M>
 where
     T: theme::Theme<'a>,
 {
@@ -27,41 +30,91 @@ where
     fn layout_state(&self) -> &layout::State;
 
     fn layout_state_mut(&mut self) -> &mut layout::State;
+
+    /// Whether this widget can receive focus and therefore navigation and editing events.
+    ///
+    /// Focusable widgets also receive the focus when they handle a pointer press.
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// The layer that this widget is drawn in, e.g. to draw a popup over the widgets around it.
+    ///
+    /// Widgets in higher layers are drawn after and hit by the pointer before those in lower
+    /// layers.  Descendants are in the same layer as their parent unless they return a higher one.
+    fn layer(&self) -> u8 {
+        0
+    }
+
+    /// Whether this widget blocks pointer input to lower layers, so that e.g. presses outside of
+    /// a popup are delivered to the popup, which can close itself.
+    ///
+    /// The focus is also kept in the topmost modal widget or on one of its ancestors; see
+    /// `focus::confine`.
+    fn is_modal(&self) -> bool {
+        false
+    }
+
+    /// How far the children of this widget are moved up and to the left from where they were laid
+    /// out, e.g. by scrolling.
+    fn scroll_offset(&self) -> layout::Point {
+        layout::Point { x: 0.0, y: 0.0 }
+    }
+
+    /// Whether the descendants of this widget are only drawn and hit within its bounds.
+    fn clips_children(&self) -> bool {
+        false
+    }
+
+    /// Advances animations, e.g. kinetic scrolling, to the time `AnimationContext::now`.
+    ///
+    /// This is called for every widget once per frame, after the tree is laid out and before it is
+    /// drawn.
+    fn animate(&mut self, _context: &AnimationContext) {}
+
+    /// Handles an event targeted at this widget or at one of its descendants, returning whether it
+    /// was handled.
+    ///
+    /// `EventContext::phase` tells which of the two it is; see `event::Phase`.  Messages for the
+    /// application can be emitted via `EventContext::emit`.
+    fn handle_event(&mut self, _context: &mut EventContext<T, M>, _event: &event::Event) -> bool {
+        false
+    }
 }
 
-pub trait Visitor<'a, T>: Sized
+pub trait Visitor<'a, T, M>: Sized
 where
     T: theme::Theme<'a>,
 {
     type Output;
-    type ChildVisitor: ChildVisitor<'a, T, Output = Self::Output>;
+    type ChildVisitor: ChildVisitor<'a, T, M, Output = Self::Output>;
 
     fn accept_leaf<N>(self, node: &N) -> Self::Output
     where
-        N: Widget<'a, T>;
+        N: Widget<'a, T, M>;
 
     fn accept_node<N>(self, node: &N, child_count: usize) -> Self::ChildVisitor
     where
-        N: Widget<'a, T>;
+        N: Widget<'a, T, M>;
 }
 
-pub trait MutVisitor<'a, T>: Sized
+pub trait MutVisitor<'a, T, M>: Sized
 where
     T: theme::Theme<'a>,
 {
     type Output;
-    type MutChildVisitor: MutChildVisitor<'a, T, Output = Self::Output>;
+    type MutChildVisitor: MutChildVisitor<'a, T, M, Output = Self::Output>;
 
     fn accept_leaf_mut<N>(self, node: &mut N) -> Self::Output
     where
-        N: Widget<'a, T>;
+        N: Widget<'a, T, M>;
 
     fn accept_node_mut<N>(self, node: &mut N, child_count: usize) -> Self::MutChildVisitor
     where
-        N: Widget<'a, T>;
+        N: Widget<'a, T, M>;
 }
 
-pub trait ChildVisitor<'a, T>
+pub trait ChildVisitor<'a, T, M>
 where
     T: theme::Theme<'a>,
 {
@@ -69,12 +122,12 @@ where
 
     fn accept_child<W>(&mut self, widget: &W)
     where
-        W: Node<'a, T>;
+        W: Node<'a, T, M>;
 
     fn end(self) -> Self::Output;
 }
 
-pub trait MutChildVisitor<'a, T>
+pub trait MutChildVisitor<'a, T, M>
 where
     T: theme::Theme<'a>,
 {
@@ -82,13 +135,90 @@ where
 
     fn accept_child_mut<W>(&mut self, widget: &mut W)
     where
-        W: Node<'a, T>;
+        W: Node<'a, T, M>;
 
     fn end(self) -> Self::Output;
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Id(pub(crate) stretch::node::Node);
+
 pub struct DrawContext<'a, T> {
     pub theme: &'a T,
     pub position: embedded_graphics::geometry::Point,
     pub size: embedded_graphics::geometry::Size,
+    pub focused: bool,
+    /// Whether the pointer is over this widget; only reported by pointing devices that can hover,
+    /// or while a touch panel is being touched.
+    pub hovered: bool,
+    pub(crate) layout: &'a layout::Layout,
+}
+
+impl<'a, T> DrawContext<'a, T> {
+    /// The size of the widget that owns `state`, e.g. a child of this widget.
+    pub fn size_of(&self, state: &layout::State) -> Option<embedded_graphics::geometry::Size> {
+        let geometry = self.layout.get_geometry(state)?;
+        Some(geometry.embedded_size())
+    }
+}
+
+pub struct AnimationContext<'a> {
+    pub size: embedded_graphics::geometry::Size,
+    /// The time of the frame being drawn, as returned by the application clock.
+    pub now: time::Duration,
+    /// The widget that has the focus, if any.
+    pub focus: Option<Id>,
+    pub(crate) layout: &'a layout::Layout,
+}
+
+impl<'a> AnimationContext<'a> {
+    /// The size of the widget that owns `state`, e.g. a child of this widget.
+    pub fn size_of(&self, state: &layout::State) -> Option<embedded_graphics::geometry::Size> {
+        let geometry = self.layout.get_geometry(state)?;
+        Some(geometry.embedded_size())
+    }
+}
+
+pub struct EventContext<'a, T, M> {
+    pub theme: &'a T,
+    pub position: embedded_graphics::geometry::Point,
+    pub size: embedded_graphics::geometry::Size,
+    /// Whether the event is being captured by, delivered to, or bubbling up from this widget.
+    pub phase: event::Phase,
+    /// The time at which the event happened, as returned by the application clock.
+    pub now: time::Duration,
+    pub messages: &'a mut message::Queue<M>,
+    pub events: &'a mut event::Queue,
+    pub(crate) layout: &'a layout::Layout,
+}
+
+impl<'a, T, M> EventContext<'a, T, M> {
+    /// Emits a message to the application.
+    ///
+    /// Messages that do not fit in the queue are dropped.
+    pub fn emit(&mut self, message: M) {
+        let _ = self.messages.push(message);
+    }
+
+    /// Posts an event to be handled after the current one, as if it came from the input source,
+    /// e.g. to type into the focused widget from an on-screen keyboard.
+    ///
+    /// Events that do not fit in the queue are dropped.
+    pub fn post(&mut self, event: event::Event) {
+        let _ = self.events.push(event);
+    }
+
+    /// Whether `point` is within the bounds of the widget, e.g. to tell whether the pointer was
+    /// released over a widget that captured it.
+    pub fn contains(&self, point: embedded_graphics::geometry::Point) -> bool {
+        let x = point.x - self.position.x;
+        let y = point.y - self.position.y;
+        x >= 0 && y >= 0 && (x as u32) < self.size.width && (y as u32) < self.size.height
+    }
+
+    /// The size of the widget that owns `state`, e.g. a child of this widget.
+    pub fn size_of(&self, state: &layout::State) -> Option<embedded_graphics::geometry::Size> {
+        let geometry = self.layout.get_geometry(state)?;
+        Some(geometry.embedded_size())
+    }
 }
@@ -1,5 +1,24 @@
 pub mod button;
+pub mod calibration;
+pub mod checkbox;
 pub mod container;
+pub mod dialog;
+pub mod dropdown;
 pub mod frame;
+pub mod image;
+pub mod keyboard;
+pub mod list_view;
+pub mod menu;
 pub mod progress_bar;
+pub mod radio_group;
+mod rows;
+pub mod scroll;
+pub mod slider;
+pub mod stepper;
+pub mod switch;
+pub mod table;
+pub mod tabs;
 pub mod text;
+pub mod text_input;
+pub mod toast;
+pub mod toggle;
@@ -1,3 +1,4 @@
+use crate::event;
 use crate::layout;
 use crate::theme;
 use crate::util;
@@ -10,41 +11,101 @@ pub struct State {
 }
 
 #[derive(Debug)]
-pub struct Button<'a> {
+pub struct Button<'a, M> {
     state: &'a mut State,
     text: &'a str,
+    on_press: Option<M>,
+    disabled: bool,
 }
 
-impl<'a> Button<'a> {
+/// The interaction state a button is drawn in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interaction {
+    Idle,
+    Hovered,
+    Pressed,
+    Focused,
+    Disabled,
+}
+
+impl<'a, M> Button<'a, M> {
     pub fn new(state: &'a mut State, text: &'a str) -> Self {
-        Self { state, text }
+        let on_press = None;
+        let disabled = false;
+        Self {
+            state,
+            text,
+            on_press,
+            disabled,
+        }
+    }
+
+    /// Sets the message that is emitted when the button is pressed.
+    pub fn on_press(mut self, message: M) -> Self {
+        self.on_press = Some(message);
+        self
+    }
+
+    /// Disables the button, so that it can't be focused and ignores all events.
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// The interaction state of the button, given whether it is focused and hovered.
+    ///
+    /// A press only shows while the pointer is over the button, so that it's visible that
+    /// releasing the pointer elsewhere will not press it.
+    pub fn interaction(&self, focused: bool, hovered: bool) -> Interaction {
+        if self.disabled {
+            Interaction::Disabled
+        } else if self.state.pressed && hovered {
+            Interaction::Pressed
+        } else if hovered {
+            Interaction::Hovered
+        } else if focused {
+            Interaction::Focused
+        } else {
+            Interaction::Idle
+        }
+    }
+
+    fn press<T>(&self, context: &mut widget::EventContext<T, M>)
+    where
+        M: Clone,
+    {
+        if let Some(message) = &self.on_press {
+            context.emit(message.clone());
+        }
     }
 }
 
-impl<'a, T> widget::Node<'a, T> for Button<'a>
+impl<'a, T, M> widget::Node<'a, T, M> for Button<'a, M>
 where
     T: theme::Theme<'a>,
+    M: Clone,
 {
     fn visit<V>(&self, visitor: V) -> V::Output
     where
-        V: widget::Visitor<'a, T>,
+        V: widget::Visitor<'a, T, M>,
     {
         visitor.accept_leaf(self)
     }
 
     fn visit_mut<V>(&mut self, visitor: V) -> V::Output
     where
-        V: widget::MutVisitor<'a, T>,
+        V: widget::MutVisitor<'a, T, M>,
     {
         visitor.accept_leaf_mut(self)
     }
 }
 
-impl<'a, T> widget::Widget<'a, T> for Button<'a>
+impl<'a, T, M> widget::Widget<'a, T, M> for Button<'a, M>
 where
     T: theme::Theme<'a>,
+    M: Clone,
 {
-    type Output = core::iter::Chain<util::FontIter<'a, T>, util::RectIter<T::Color>>;
+    type Output = core::iter::Chain<util::RectIter<T::Color>, util::FontIter<'a, T>>;
 
     fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
         use embedded_graphics::fonts::Font;
@@ -57,24 +118,35 @@ where
         let x2 = x1 + context.size.width as i32 - 1;
         let y2 = y1 + context.size.height as i32 - 1;
 
+        let theme = context.theme;
+        let (fill, border, text_color) = match self.interaction(context.focused, context.hovered) {
+            Interaction::Idle => (None, theme.border_color(), theme.text_color()),
+            Interaction::Hovered => (
+                Some(theme.hover_color()),
+                theme.border_color(),
+                theme.text_color(),
+            ),
+            Interaction::Pressed => (
+                Some(theme.pressed_color()),
+                theme.border_color(),
+                theme.background_color(),
+            ),
+            Interaction::Focused => (None, theme.focus_color(), theme.text_color()),
+            Interaction::Disabled => (None, theme.disabled_color(), theme.disabled_color()),
+        };
+
         let text = T::Font::render_str(&self.text);
         let text_size = text.size();
         let xt = x1 + (context.size.width as i32 - text_size.width as i32) / 2;
         let yt = y1 + (context.size.height as i32 - text_size.height as i32) / 2;
 
-        text.translate(embedded_graphics::geometry::Point::new(xt, yt))
-            .stroke(Some(context.theme.text_color()))
+        embedded_graphics::egrectangle!((x1, y1), (x2, y2), fill = fill, stroke = Some(border))
             .into_iter()
-            .chain(embedded_graphics::egrectangle!(
-                (x1, y1),
-                (x2, y2),
-                fill = if self.state.pressed {
-                    Some(context.theme.border_color())
-                } else {
-                    None
-                },
-                stroke = Some(context.theme.border_color()),
-            ))
+            .chain(
+                text.translate(embedded_graphics::geometry::Point::new(xt, yt))
+                    .stroke(Some(text_color))
+                    .into_iter(),
+            )
     }
 
     fn layout_style(&self, theme: &T) -> layout::Style {
@@ -92,4 +164,41 @@ where
     fn layout_state_mut(&mut self) -> &mut layout::State {
         &mut self.state.layout
     }
+
+    fn is_focusable(&self) -> bool {
+        !self.disabled
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        if self.disabled {
+            self.state.pressed = false;
+            return false;
+        }
+
+        match event {
+            event::Event::Pointer(pointer) => match pointer.action {
+                event::PointerAction::Down => {
+                    self.state.pressed = true;
+                    true
+                }
+                event::PointerAction::Up => {
+                    if self.state.pressed && context.contains(pointer.position) {
+                        self.press(context);
+                    }
+                    self.state.pressed = false;
+                    true
+                }
+                event::PointerAction::Move => false,
+            },
+            event::Event::Navigation(event::Navigation::Activate) => {
+                self.press(context);
+                true
+            }
+            _ => false,
+        }
+    }
 }
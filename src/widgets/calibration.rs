@@ -0,0 +1,199 @@
+//! A screen for three-point touch panel calibration.
+use crate::event;
+use crate::input::calibration;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use embedded_graphics::egline;
+
+const CROSSHAIR_RADIUS: i32 = 6;
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    samples: [embedded_graphics::geometry::Point; 3],
+    step: usize,
+}
+
+/// A widget that fills its parent and asks the user to touch three targets in turn.
+///
+/// The pointer positions it receives are expected to have been transformed with the `current`
+/// calibration matrix, e.g. by an `input::calibration::Calibrated` source; when calibrating for the
+/// first time, this should be a rough mapping such as `Matrix::scale` that puts the touches
+/// somewhere on the screen.  Once all targets have been touched, the widget emits the new matrix,
+/// which replaces `current` and can be persisted.
+#[derive(Debug)]
+pub struct Calibration<'a, M> {
+    state: &'a mut State,
+    current: calibration::Matrix,
+    text: &'a str,
+    on_complete: Option<fn(calibration::Matrix) -> M>,
+}
+
+impl State {
+    /// Starts over with the first target.
+    pub fn restart(&mut self) {
+        self.step = 0;
+    }
+}
+
+impl<'a, M> Calibration<'a, M> {
+    pub fn new(state: &'a mut State, current: calibration::Matrix) -> Self {
+        let text = "Touch the target";
+        let on_complete = None;
+        Self {
+            state,
+            current,
+            text,
+            on_complete,
+        }
+    }
+
+    /// Sets the instructions that are shown to the user.
+    pub fn text(mut self, text: &'a str) -> Self {
+        self.text = text;
+        self
+    }
+
+    /// Sets the function that creates the message emitted with the new calibration matrix.
+    pub fn on_complete(mut self, on_complete: fn(calibration::Matrix) -> M) -> Self {
+        self.on_complete = Some(on_complete);
+        self
+    }
+
+    fn sample<T>(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        position: embedded_graphics::geometry::Point,
+    ) {
+        let step = self.state.step;
+        self.state.samples[step] = position;
+        if step + 1 < self.state.samples.len() {
+            self.state.step += 1;
+            return;
+        }
+
+        // If the touches were on a line, the user has to try again
+        self.state.step = 0;
+        let display = targets(context.position, context.size);
+        if let Some(matrix) = calibration::Matrix::from_points(self.state.samples, display) {
+            if let Some(on_complete) = self.on_complete {
+                context.emit(on_complete(self.current.then(&matrix)));
+            }
+        }
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Calibration<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Calibration<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<util::LineIter<T::Color>, util::LineIter<T::Color>>,
+        util::FontIter<'a, T>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let target = targets(context.position, context.size)[self.state.step];
+        let color = context.theme.text_color();
+
+        let text = T::Font::render_str(&self.text);
+        let text_size = text.size();
+        let xt = context.position.x + (context.size.width as i32 - text_size.width as i32) / 2;
+        let yt = context.position.y + (context.size.height as i32 - text_size.height as i32) / 2;
+
+        egline!(
+            (target.x - CROSSHAIR_RADIUS, target.y),
+            (target.x + CROSSHAIR_RADIUS, target.y),
+            stroke = Some(color)
+        )
+        .into_iter()
+        .chain(egline!(
+            (target.x, target.y - CROSSHAIR_RADIUS),
+            (target.x, target.y + CROSSHAIR_RADIUS),
+            stroke = Some(color)
+        ))
+        .chain(
+            text.translate(embedded_graphics::geometry::Point::new(xt, yt))
+                .stroke(Some(color))
+                .into_iter(),
+        )
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        // Without margins, so that the targets are where the screen expects them
+        layout::Style {
+            flex_grow: 1.0,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Percent(1.0),
+                height: stretch::style::Dimension::Percent(1.0),
+            },
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        match event {
+            event::Event::Pointer(pointer) => {
+                // Sample where the touch ends, as the reading is most stable by then
+                if pointer.action == event::PointerAction::Up {
+                    self.sample(context, pointer.position);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The targets, in display coordinates, for a calibration screen at `position` with size `size`.
+fn targets(
+    position: embedded_graphics::geometry::Point,
+    size: embedded_graphics::geometry::Size,
+) -> [embedded_graphics::geometry::Point; 3] {
+    let at = |x: u32, y: u32| {
+        embedded_graphics::geometry::Point::new(
+            position.x + (size.width * x / 100) as i32,
+            position.y + (size.height * y / 100) as i32,
+        )
+    };
+    [at(15, 15), at(85, 50), at(50, 85)]
+}
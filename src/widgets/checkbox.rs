@@ -0,0 +1,69 @@
+use crate::theme;
+use crate::util;
+use crate::widget;
+use crate::widgets::switch;
+
+pub use switch::State;
+
+/// A labelled box that is checked or not.
+///
+/// The checked state is owned by the application, which should update it in response to the
+/// message emitted when the checkbox is toggled.
+pub type Checkbox<'a, M> = switch::Switch<'a, M, Check>;
+
+/// The box of a checkbox, which is filled when it is checked.
+#[derive(Debug)]
+pub struct Check;
+
+impl<'a, T> switch::Indicator<'a, T> for Check
+where
+    T: theme::Theme<'a>,
+{
+    type Output =
+        core::iter::Chain<util::RectIter<T::Color>, util::OptionalIter<util::RectIter<T::Color>>>;
+
+    fn width() -> i32 {
+        1
+    }
+
+    fn draw(
+        on: bool,
+        context: &widget::DrawContext<T>,
+        top_left: embedded_graphics::geometry::Point,
+        side: i32,
+    ) -> Self::Output {
+        let theme = context.theme;
+        let (x1, y1) = (top_left.x, top_left.y);
+        let x2 = x1 + side - 1;
+        let y2 = y1 + side - 1;
+
+        // The check contrasts with the box, which is filled while hovered
+        let (fill, check_color) = if context.hovered {
+            (Some(theme.hover_color()), theme.background_color())
+        } else {
+            (None, theme.fill_color())
+        };
+        let box_iter = embedded_graphics::egrectangle!(
+            (x1, y1),
+            (x2, y2),
+            fill = fill,
+            stroke = Some(theme.border_color())
+        )
+        .into_iter();
+
+        let check_iter = if on {
+            Some(
+                embedded_graphics::egrectangle!(
+                    (x1 + 2, y1 + 2),
+                    (x2 - 2, y2 - 2),
+                    fill = Some(check_color)
+                )
+                .into_iter(),
+            )
+        } else {
+            None
+        };
+
+        box_iter.chain(check_iter.into_iter().flatten())
+    }
+}
@@ -4,11 +4,11 @@ use crate::theme;
 use crate::widget;
 use core::marker;
 
-pub type Main<'a, T, X> = Container<'a, MainBase, T, X>;
+pub type Main<'a, T, M, X> = Container<'a, MainBase, T, M, X>;
 
-pub type Row<'a, T, X> = Container<'a, RowBase, T, X>;
+pub type Row<'a, T, M, X> = Container<'a, RowBase, T, M, X>;
 
-pub type Column<'a, T, X> = Container<'a, ColumnBase, T, X>;
+pub type Column<'a, T, M, X> = Container<'a, ColumnBase, T, M, X>;
 
 #[derive(Debug, Default)]
 pub struct State {
@@ -16,10 +16,10 @@ pub struct State {
 }
 
 #[derive(Debug)]
-pub struct Container<'a, B, T, X> {
+pub struct Container<'a, B, T, M, X> {
     state: &'a mut State,
     children: X,
-    phantom: marker::PhantomData<(B, T)>,
+    phantom: marker::PhantomData<(B, T, M)>,
 }
 
 pub trait Base {
@@ -68,11 +68,11 @@ impl Base for ColumnBase {
     const VERT_SPACE_FACTOR: f32 = -1.0;
 }
 
-impl<'a, B, T, X> Container<'a, B, T, X>
+impl<'a, B, T, M, X> Container<'a, B, T, M, X>
 where
     B: Base,
     T: theme::Theme<'a>,
-    X: children::Children<'a, T>,
+    X: children::Children<'a, T, M>,
 {
     pub fn new(state: &'a mut State, children: X) -> Self {
         let phantom = marker::PhantomData;
@@ -84,15 +84,15 @@ where
     }
 }
 
-impl<'a, B, T, X> widget::Node<'a, T> for Container<'a, B, T, X>
+impl<'a, B, T, M, X> widget::Node<'a, T, M> for Container<'a, B, T, M, X>
 where
     B: Base,
     T: theme::Theme<'a>,
-    X: children::Children<'a, T>,
+    X: children::Children<'a, T, M>,
 {
     fn visit<V>(&self, visitor: V) -> V::Output
     where
-        V: widget::Visitor<'a, T>,
+        V: widget::Visitor<'a, T, M>,
     {
         let visitor = visitor.accept_node(self, self.children.len());
         self.children.visit_children(visitor)
@@ -100,18 +100,18 @@ where
 
     fn visit_mut<V>(&mut self, visitor: V) -> V::Output
     where
-        V: widget::MutVisitor<'a, T>,
+        V: widget::MutVisitor<'a, T, M>,
     {
         let visitor = visitor.accept_node_mut(self, self.children.len());
         self.children.visit_children_mut(visitor)
     }
 }
 
-impl<'a, B, T, X> widget::Widget<'a, T> for Container<'a, B, T, X>
+impl<'a, B, T, M, X> widget::Widget<'a, T, M> for Container<'a, B, T, M, X>
 where
     B: Base,
     T: theme::Theme<'a>,
-    X: children::Children<'a, T>,
+    X: children::Children<'a, T, M>,
 {
     type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
 
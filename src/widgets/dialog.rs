@@ -0,0 +1,740 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use embedded_graphics::egrectangle;
+
+/// The maximum number of buttons of a dialog.
+pub const MAX_BUTTONS: usize = 4;
+
+/// Above popups such as those of dropdowns, which may be open in the content behind a dialog.
+const DIALOG_LAYER: u8 = 2;
+
+/// A button of a dialog, which closes it when chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+}
+
+/// The state of a dialog and the widgets it is drawn with.
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    overlay: layout::State,
+    panel: layout::State,
+    selection: Selection,
+}
+
+#[derive(Debug, Default)]
+struct Selection {
+    open: bool,
+    highlighted: usize,
+    pressed: Option<usize>,
+}
+
+/// A modal dialog with a title, a text and a row of buttons, shown over other content.
+///
+/// The dialog wraps the content that it is shown over, and is meant to be the root of the widget
+/// tree so that it is centered on the screen.  While it is open, the content is hatched with the
+/// background color, which dims it on color displays, and the dialog captures all input: the
+/// pointer can only press its buttons, and navigation events move between the buttons whichever
+/// widget has the focus.  `Activate` chooses the highlighted button, and `Back` chooses `Cancel`
+/// or `No` if there is such a button.  Choosing a button closes the dialog and emits a message
+/// with the button.
+#[derive(Debug)]
+pub struct Dialog<'a, N, M> {
+    layout: &'a mut layout::State,
+    content: N,
+    overlay: Overlay<'a, M>,
+}
+
+/// The area over the content that the dialog is centered in, and which takes all pointer input
+/// outside of it.
+#[derive(Debug)]
+struct Overlay<'a, M> {
+    layout: &'a mut layout::State,
+    panel: Panel<'a, M>,
+}
+
+/// The dialog itself.
+#[derive(Debug)]
+struct Panel<'a, M> {
+    layout: &'a mut layout::State,
+    selection: &'a mut Selection,
+    title: &'a str,
+    body: &'a str,
+    buttons: &'a [Button],
+    on_close: Option<fn(Button) -> M>,
+}
+
+/// The pixels of the hatching over the content behind a dialog.
+pub struct Hatch<C> {
+    x1: i32,
+    x2: i32,
+    y2: i32,
+    x: i32,
+    y: i32,
+    color: C,
+}
+
+/// The parts of a dialog.
+pub struct Contents<'a, T>
+where
+    T: theme::Theme<'a>,
+{
+    title: &'a str,
+    lines: core::str::Lines<'a>,
+    line: i32,
+    buttons: &'a [Button],
+    metrics: Metrics,
+    position: embedded_graphics::geometry::Point,
+    highlighted: usize,
+    pressed: Option<usize>,
+    background_color: T::Color,
+    border_color: T::Color,
+    text_color: T::Color,
+    fill_color: T::Color,
+    pressed_color: T::Color,
+    step: usize,
+}
+
+/// The dimensions of a dialog, in pixels relative to its top left corner.
+#[derive(Clone, Copy, Debug)]
+struct Metrics {
+    width: i32,
+    height: i32,
+    padding: i32,
+    line_height: i32,
+    title_height: i32,
+    buttons_x: i32,
+    buttons_y: i32,
+    button_width: i32,
+    button_height: i32,
+}
+
+impl Button {
+    pub fn label(self) -> &'static str {
+        match self {
+            Button::Ok => "OK",
+            Button::Cancel => "Cancel",
+            Button::Yes => "Yes",
+            Button::No => "No",
+        }
+    }
+}
+
+impl State {
+    /// Opens the dialog, highlighting its first button.
+    pub fn open(&mut self) {
+        self.selection.open = true;
+        self.selection.highlighted = 0;
+        self.selection.pressed = None;
+    }
+
+    pub fn close(&mut self) {
+        self.selection.open = false;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.selection.open
+    }
+}
+
+impl<'a, N, M> Dialog<'a, N, M> {
+    /// Creates a dialog shown over `content` while it is open, with a single OK button.
+    pub fn new(state: &'a mut State, content: N) -> Self {
+        let State {
+            layout,
+            overlay,
+            panel,
+            selection,
+        } = state;
+        let panel = Panel {
+            layout: panel,
+            selection,
+            title: "",
+            body: "",
+            buttons: &[Button::Ok],
+            on_close: None,
+        };
+        let overlay = Overlay {
+            layout: overlay,
+            panel,
+        };
+        Self {
+            layout,
+            content,
+            overlay,
+        }
+    }
+
+    pub fn title(mut self, title: &'a str) -> Self {
+        self.overlay.panel.title = title;
+        self
+    }
+
+    /// Sets the text of the dialog, which is split into lines at line breaks.
+    pub fn body(mut self, body: &'a str) -> Self {
+        self.overlay.panel.body = body;
+        self
+    }
+
+    /// Sets the buttons of the dialog, of which only the first `MAX_BUTTONS` are shown.
+    pub fn buttons(mut self, buttons: &'a [Button]) -> Self {
+        let count = buttons.len().min(MAX_BUTTONS);
+        self.overlay.panel.buttons = &buttons[..count];
+        self.overlay.panel.selection.highlighted = self
+            .overlay
+            .panel
+            .selection
+            .highlighted
+            .min(count.saturating_sub(1));
+        self
+    }
+
+    /// Sets the function that creates the message emitted with the button that closed the dialog.
+    pub fn on_close(mut self, on_close: fn(Button) -> M) -> Self {
+        self.overlay.panel.on_close = Some(on_close);
+        self
+    }
+}
+
+impl<'a, M> Panel<'a, M> {
+    fn choose<T>(&mut self, context: &mut widget::EventContext<T, M>, index: usize) {
+        if let Some(&button) = self.buttons.get(index) {
+            self.selection.open = false;
+            if let Some(on_close) = self.on_close {
+                context.emit(on_close(button));
+            }
+        }
+    }
+
+    fn navigate<T>(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        navigation: event::Navigation,
+    ) {
+        let highlighted = self.selection.highlighted;
+        match navigation {
+            event::Navigation::Activate => self.choose(context, highlighted),
+            event::Navigation::Back => {
+                let cancel = self
+                    .buttons
+                    .iter()
+                    .position(|&button| button == Button::Cancel || button == Button::No);
+                if let Some(index) = cancel {
+                    self.choose(context, index);
+                }
+            }
+            event::Navigation::Next | event::Navigation::Right | event::Navigation::Down => {
+                if highlighted + 1 < self.buttons.len() {
+                    self.selection.highlighted += 1;
+                }
+            }
+            event::Navigation::Previous | event::Navigation::Left | event::Navigation::Up => {
+                self.selection.highlighted = highlighted.saturating_sub(1);
+            }
+        }
+    }
+
+    /// The button under `point`, if any.
+    fn button_at<T>(
+        &self,
+        context: &widget::EventContext<T, M>,
+        metrics: &Metrics,
+        point: embedded_graphics::geometry::Point,
+    ) -> Option<usize> {
+        let x = point.x - context.position.x - metrics.buttons_x;
+        let y = point.y - context.position.y - metrics.buttons_y;
+        let stride = metrics.button_width + metrics.padding;
+        if x < 0 || y < 0 || y >= metrics.button_height || x % stride >= metrics.button_width {
+            return None;
+        }
+        Some((x / stride) as usize).filter(|&index| index < self.buttons.len())
+    }
+}
+
+impl<'a, N, T, M> widget::Node<'a, T, M> for Dialog<'a, N, M>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let open = self.overlay.panel.selection.open;
+        let mut visitor = visitor.accept_node(self, 1 + open as usize);
+        visitor.accept_child(&self.content);
+        if open {
+            visitor.accept_child(&self.overlay);
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let open = self.overlay.panel.selection.open;
+        let mut visitor = visitor.accept_node_mut(self, 1 + open as usize);
+        visitor.accept_child_mut(&mut self.content);
+        if open {
+            visitor.accept_child_mut(&mut self.overlay);
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, N, T, M> widget::Widget<'a, T, M> for Dialog<'a, N, M>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        layout::Style {
+            flex_grow: 1.0,
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_items: stretch::style::AlignItems::Stretch,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Percent(1.0),
+                height: stretch::style::Dimension::Percent(1.0),
+            },
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let panel = &mut self.overlay.panel;
+        if !panel.selection.open || context.phase != event::Phase::Capture {
+            return false;
+        }
+
+        // Events for the focused widget are taken away from it, wherever it is
+        match event {
+            event::Event::Navigation(navigation) => {
+                panel.navigate(context, *navigation);
+                true
+            }
+            event::Event::Edit(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Overlay<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, 1);
+        visitor.accept_child(&self.panel);
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let mut visitor = visitor.accept_node_mut(self, 1);
+        visitor.accept_child_mut(&mut self.panel);
+        visitor.end()
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Overlay<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = Hatch<T::Color>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        Hatch {
+            x1,
+            x2: x1 + context.size.width as i32 - 1,
+            y2: y1 + context.size.height as i32 - 1,
+            x: x1,
+            y: y1,
+            color: context.theme.background_color(),
+        }
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        layout::Style {
+            position_type: stretch::style::PositionType::Absolute,
+            position: stretch::geometry::Rect {
+                start: stretch::style::Dimension::Points(0.0),
+                top: stretch::style::Dimension::Points(0.0),
+                ..Default::default()
+            },
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Percent(1.0),
+                height: stretch::style::Dimension::Percent(1.0),
+            },
+            justify_content: stretch::style::JustifyContent::Center,
+            align_items: stretch::style::AlignItems::Center,
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+
+    fn layer(&self) -> u8 {
+        DIALOG_LAYER
+    }
+
+    fn is_modal(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        // Pointer input outside of the dialog is swallowed
+        match (context.phase, event) {
+            (event::Phase::Capture, _) => false,
+            (_, event::Event::Pointer(_)) | (_, event::Event::Gesture(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Panel<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Panel<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = util::Pixels<'a, T, Contents<'a, T>>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let theme = context.theme;
+        let metrics = Metrics::new(theme, self.title, self.body, self.buttons);
+        util::Pixels::new(Contents {
+            title: self.title,
+            lines: self.body.lines(),
+            line: 0,
+            buttons: self.buttons,
+            metrics,
+            position: context.position,
+            highlighted: self.selection.highlighted,
+            pressed: self.selection.pressed.filter(|_| context.hovered),
+            background_color: theme.background_color(),
+            border_color: theme.border_color(),
+            text_color: theme.text_color(),
+            fill_color: if context.focused {
+                theme.focus_color()
+            } else {
+                theme.fill_color()
+            },
+            pressed_color: theme.pressed_color(),
+            step: 0,
+        })
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        let metrics = Metrics::new(theme, self.title, self.body, self.buttons);
+        layout::Style {
+            flex_shrink: 0.0,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(metrics.width as f32),
+                height: stretch::style::Dimension::Points(metrics.height as f32),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let pointer = match (context.phase, event) {
+            (event::Phase::Capture, _) => return false,
+            (_, event::Event::Pointer(pointer)) => pointer,
+            (_, event::Event::Gesture(_)) => return true,
+            _ => return false,
+        };
+
+        let metrics = Metrics::new(context.theme, self.title, self.body, self.buttons);
+        let button = self.button_at(context, &metrics, pointer.position);
+        match pointer.action {
+            event::PointerAction::Down => {
+                self.selection.pressed = button;
+                if let Some(index) = button {
+                    self.selection.highlighted = index;
+                }
+            }
+            event::PointerAction::Move => {}
+            event::PointerAction::Up => {
+                if let Some(index) = self.selection.pressed.take().filter(|&i| Some(i) == button) {
+                    self.choose(context, index);
+                }
+            }
+        }
+        true
+    }
+}
+
+impl Metrics {
+    fn new<'a, T>(theme: &T, title: &'a str, body: &'a str, buttons: &[Button]) -> Self
+    where
+        T: theme::Theme<'a>,
+    {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let text_width = |text: &'a str| T::Font::render_str(text).size().width as i32;
+        let glyph_height = T::Font::render_str("0").size().height as i32;
+        let padding = theme.spacing() as i32;
+
+        let line_height = glyph_height + padding;
+        let title_height = glyph_height + 2 * padding;
+        let button_height = glyph_height + 2 * padding;
+        let button_width = buttons
+            .iter()
+            .map(|button| text_width(button.label()))
+            .max()
+            .unwrap_or(0)
+            + 4 * padding;
+        let buttons_width = buttons.len() as i32 * (button_width + padding) - padding;
+
+        let lines = body.lines().count() as i32;
+        let body_width = body.lines().map(text_width).max().unwrap_or(0);
+        let content_width = text_width(title).max(body_width).max(buttons_width);
+        let width = content_width + 2 * padding + 2;
+
+        // The border, the title and the line under it, the body and the buttons
+        let buttons_y = 1 + title_height + 1 + padding + lines * line_height;
+        let height = buttons_y + button_height + padding + 1;
+        let buttons_x = (width - buttons_width) / 2;
+
+        Metrics {
+            width,
+            height,
+            padding,
+            line_height,
+            title_height,
+            buttons_x,
+            buttons_y,
+            button_width,
+            button_height,
+        }
+    }
+}
+
+impl<C> Iterator for Hatch<C>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+{
+    type Item = embedded_graphics::drawable::Pixel<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.y > self.y2 {
+            return None;
+        }
+
+        // Every other pixel, in a checkerboard pattern
+        if (self.x + self.y) & 1 != 0 {
+            self.x += 1;
+        }
+        while self.x > self.x2 {
+            if self.y >= self.y2 {
+                return None;
+            }
+            self.y += 1;
+            self.x = self.x1 + ((self.x1 + self.y) & 1);
+        }
+
+        let point = embedded_graphics::geometry::Point::new(self.x, self.y);
+        self.x += 2;
+        Some(embedded_graphics::drawable::Pixel(point, self.color))
+    }
+}
+
+impl<'a, T> util::Parts<'a, T> for Contents<'a, T>
+where
+    T: theme::Theme<'a>,
+{
+    /// Nothing is drawn as `util::Part::Glyphs`.
+    type Capacity = heapless::consts::U0;
+
+    /// Moves on to the next part of the dialog: its background, border, title, the line under
+    /// it, the lines of the body and the buttons.
+    fn advance(&mut self) -> Option<util::Part<'a, T, Self::Capacity>> {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let metrics = self.metrics;
+        let x1 = self.position.x;
+        let y1 = self.position.y;
+        let x2 = x1 + metrics.width - 1;
+        let y2 = y1 + metrics.height - 1;
+        let text = |text: &'a str, x: i32, y: i32, color: T::Color| {
+            util::Part::Text(
+                T::Font::render_str(text)
+                    .translate(embedded_graphics::geometry::Point::new(x, y))
+                    .stroke(Some(color))
+                    .into_iter(),
+            )
+        };
+
+        let step = self.step;
+        self.step += 1;
+        let part = match step {
+            0 => util::Part::Rect(
+                egrectangle!((x1, y1), (x2, y2), fill = Some(self.background_color)).into_iter(),
+            ),
+            1 => util::Part::Rect(
+                egrectangle!((x1, y1), (x2, y2), stroke = Some(self.border_color)).into_iter(),
+            ),
+            2 => text(
+                self.title,
+                x1 + 1 + metrics.padding,
+                y1 + 1 + metrics.padding,
+                self.text_color,
+            ),
+            3 => {
+                let y = y1 + 1 + metrics.title_height;
+                util::Part::Rect(
+                    egrectangle!((x1, y), (x2, y), stroke = Some(self.border_color)).into_iter(),
+                )
+            }
+            _ => {
+                // Then the lines of the body, one step each until there are none left
+                if let Some(line) = self.lines.next() {
+                    self.step = 4;
+                    let index = self.line;
+                    self.line += 1;
+                    let y = y1
+                        + 1
+                        + metrics.title_height
+                        + 1
+                        + metrics.padding
+                        + index * metrics.line_height;
+                    return Some(text(line, x1 + 1 + metrics.padding, y, self.text_color));
+                }
+
+                // And finally, two steps for every button
+                let index = (step - 4) / 2;
+                let button = *self.buttons.get(index)?;
+                let bx = x1
+                    + metrics.buttons_x
+                    + index as i32 * (metrics.button_width + metrics.padding);
+                let by = y1 + metrics.buttons_y;
+                let fill = if self.pressed == Some(index) {
+                    Some(self.pressed_color)
+                } else if self.highlighted == index {
+                    Some(self.fill_color)
+                } else {
+                    None
+                };
+                if step % 2 == 0 {
+                    util::Part::Rect(
+                        egrectangle!(
+                            (bx, by),
+                            (
+                                bx + metrics.button_width - 1,
+                                by + metrics.button_height - 1
+                            ),
+                            fill = fill,
+                            stroke = Some(self.border_color)
+                        )
+                        .into_iter(),
+                    )
+                } else {
+                    let label = button.label();
+                    let size = T::Font::render_str(label).size();
+                    let color = if fill.is_some() {
+                        self.background_color
+                    } else {
+                        self.text_color
+                    };
+                    text(
+                        label,
+                        bx + (metrics.button_width - size.width as i32) / 2,
+                        by + metrics.padding,
+                        color,
+                    )
+                }
+            }
+        };
+        Some(part)
+    }
+}
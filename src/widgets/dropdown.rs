@@ -0,0 +1,610 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::borrow::Borrow;
+use core::borrow::BorrowMut;
+use embedded_graphics::egrectangle;
+
+/// The maximum number of options in a dropdown; any further options are ignored.
+pub type Capacity = heapless::consts::U8;
+
+/// The layer that the list of options is drawn in.
+const POPUP_LAYER: u8 = 1;
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    popup: PopupState,
+    selection: Selection,
+}
+
+#[derive(Clone, Debug, Default)]
+struct PopupState {
+    layout: layout::State,
+    options: heapless::Vec<layout::State, Capacity>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Selection {
+    selected: usize,
+    highlighted: usize,
+    open: bool,
+    pressed: Option<usize>,
+}
+
+/// A box showing the selected option, which opens a list of all options when activated.
+///
+/// The list is drawn in a popup below the box, over the widgets around it.  It closes when an
+/// option is selected or when the pointer is pressed outside of it.  With an encoder, `Activate`
+/// opens the list, `Next` and `Previous` or the arrow keys move the highlight while it is open, and
+/// `Activate` selects the highlighted option.  `Back` closes the list without changing the
+/// selection.
+#[derive(Debug)]
+pub struct Dropdown<'a, M> {
+    state: &'a mut State,
+    options: &'a [&'a str],
+    on_select: Option<fn(usize) -> M>,
+}
+
+/// The list of options, which borrows its state from the dropdown's `State` while it is visited, or
+/// holds copies of it if the visitor doesn't need to modify it.
+struct Popup<'a, P, S, M> {
+    state: P,
+    selection: S,
+    options: &'a [&'a str],
+    on_select: Option<fn(usize) -> M>,
+}
+
+/// A single option in the list, like the list itself.
+struct Item<'a, L, S, M> {
+    layout: L,
+    selection: S,
+    label: &'a str,
+    index: usize,
+    on_select: Option<fn(usize) -> M>,
+}
+
+impl State {
+    /// The index of the selected option.
+    pub fn selected(&self) -> usize {
+        self.selection.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selection.selected = index;
+    }
+
+    /// Whether the list of options is open.
+    pub fn is_open(&self) -> bool {
+        self.selection.open
+    }
+
+    pub fn close(&mut self) {
+        self.selection.open = false;
+        self.selection.pressed = None;
+    }
+}
+
+impl Selection {
+    fn open(&mut self) {
+        self.open = true;
+        self.highlighted = self.selected;
+    }
+
+    /// Selects the option at `index` and closes the list.
+    fn choose<T, M>(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        index: usize,
+        on_select: Option<fn(usize) -> M>,
+    ) {
+        self.selected = index;
+        self.open = false;
+        self.pressed = None;
+        if let Some(on_select) = on_select {
+            context.emit(on_select(index));
+        }
+    }
+}
+
+impl<'a, M> Dropdown<'a, M> {
+    pub fn new(state: &'a mut State, options: &'a [&'a str]) -> Self {
+        // The options may have changed while the list was open
+        let len = options.len().min(state.popup.options.capacity());
+        let selection = &mut state.selection;
+        if len == 0 {
+            selection.open = false;
+            selection.pressed = None;
+        }
+        selection.highlighted = selection.highlighted.min(len.saturating_sub(1));
+
+        let on_select = None;
+        Self {
+            state,
+            options,
+            on_select,
+        }
+    }
+
+    /// Sets the function that creates the message emitted with the index of an option when it is
+    /// selected.
+    pub fn on_select(mut self, on_select: fn(usize) -> M) -> Self {
+        self.on_select = Some(on_select);
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.options.len().min(self.state.popup.options.capacity())
+    }
+
+    fn child_count(&self) -> usize {
+        if self.state.selection.open {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Dropdown<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let len = self.len();
+        let options = self.options;
+        let mut visitor = visitor.accept_node(self, self.child_count());
+        if self.state.selection.open {
+            visitor.accept_child(&Popup {
+                state: self.state.popup.clone(),
+                selection: self.state.selection,
+                options: &options[..len],
+                on_select: self.on_select,
+            });
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let len = self.len();
+        let options = self.options;
+        let options = &options[..len];
+        let on_select = self.on_select;
+
+        let mut visitor = visitor.accept_node_mut(self, self.child_count());
+        let State {
+            popup, selection, ..
+        } = &mut *self.state;
+        if selection.open {
+            visitor.accept_child_mut(&mut Popup {
+                state: popup,
+                selection,
+                options,
+                on_select,
+            });
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Dropdown<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<util::RectIter<T::Color>, util::FontIter<'a, T>>,
+        util::FontIter<'a, T>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let theme = context.theme;
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let padding = theme.spacing() as i32;
+
+        let fill = if self.state.selection.open || context.hovered {
+            Some(theme.hover_color())
+        } else {
+            None
+        };
+        let rect = egrectangle!(
+            (x1, y1),
+            (x2, y2),
+            fill = fill,
+            stroke = Some(theme.border_color())
+        );
+
+        let label = self
+            .options
+            .get(self.state.selection.selected)
+            .copied()
+            .unwrap_or("");
+        let text = T::Font::render_str(label);
+        let yt = y1 + (context.size.height as i32 - text.size().height as i32) / 2;
+        let text = text
+            .translate(embedded_graphics::geometry::Point::new(x1 + padding, yt))
+            .stroke(Some(theme.text_color()));
+
+        let arrow = T::Font::render_str("v");
+        let xa = x2 - padding - arrow.size().width as i32;
+        let arrow = arrow
+            .translate(embedded_graphics::geometry::Point::new(xa, yt))
+            .stroke(Some(theme.text_color()));
+
+        rect.into_iter()
+            .chain(text.into_iter())
+            .chain(arrow.into_iter())
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        // Leave room for the widest option, so that the box doesn't change size
+        let widest = self.options[..self.len()]
+            .iter()
+            .map(|option| T::Font::render_str(option).size().width)
+            .max()
+            .unwrap_or(0);
+        let glyph_size = T::Font::render_str("v").size();
+        let padding = theme.spacing();
+        let width = widest as f32 + glyph_size.width as f32 + 3.0 * padding;
+        let height = glyph_size.height as f32 + 2.0 * padding;
+
+        layout::Style {
+            align_self: stretch::style::AlignSelf::FlexStart,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(width),
+                height: stretch::style::Dimension::Points(height),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.len() > 0
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let phase = context.phase;
+        if phase == event::Phase::Capture {
+            return false;
+        }
+
+        // Navigation events bubble up from the list if the focus was moved into it because it is
+        // modal, e.g. after the dropdown lost the focus
+        let len = self.len();
+        let on_select = self.on_select;
+        let selection = &mut self.state.selection;
+        if len == 0 {
+            selection.open = false;
+        }
+        match event {
+            event::Event::Pointer(pointer) if phase == event::Phase::Target => {
+                if pointer.action == event::PointerAction::Down && len > 0 {
+                    selection.open();
+                }
+                true
+            }
+            event::Event::Navigation(navigation) if selection.open => {
+                match *navigation {
+                    event::Navigation::Activate => {
+                        selection.choose(context, selection.highlighted, on_select)
+                    }
+                    event::Navigation::Back => {
+                        selection.open = false;
+                        selection.pressed = None;
+                    }
+                    event::Navigation::Next | event::Navigation::Down => {
+                        selection.highlighted = (selection.highlighted + 1).min(len - 1);
+                    }
+                    event::Navigation::Previous | event::Navigation::Up => {
+                        selection.highlighted = selection.highlighted.saturating_sub(1);
+                    }
+                    // The focus stays put while the list is open
+                    event::Navigation::Left | event::Navigation::Right => {}
+                }
+                true
+            }
+            event::Event::Navigation(event::Navigation::Activate)
+                if phase == event::Phase::Target && len > 0 =>
+            {
+                selection.open();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a, P, S, T, M> widget::Node<'a, T, M> for Popup<'a, P, S, M>
+where
+    P: Borrow<PopupState> + BorrowMut<PopupState>,
+    S: Borrow<Selection> + BorrowMut<Selection>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let state = self.state.borrow();
+        let selection = *self.selection.borrow();
+        let mut visitor = visitor.accept_node(self, self.options.len());
+        for (index, &label) in self.options.iter().enumerate() {
+            visitor.accept_child(&Item {
+                layout: state.options.get(index).cloned().unwrap_or_default(),
+                selection,
+                label,
+                index,
+                on_select: self.on_select,
+            });
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let options = self.options;
+        let on_select = self.on_select;
+        let _ = self
+            .state
+            .borrow_mut()
+            .options
+            .resize_default(options.len());
+
+        let mut visitor = visitor.accept_node_mut(self, options.len());
+        let layouts = &mut self.state.borrow_mut().options;
+        let selection = self.selection.borrow_mut();
+        for (index, (layout, &label)) in layouts.iter_mut().zip(options).enumerate() {
+            visitor.accept_child_mut(&mut Item {
+                layout,
+                selection: &mut *selection,
+                label,
+                index,
+                on_select,
+            });
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, P, S, T, M> widget::Widget<'a, T, M> for Popup<'a, P, S, M>
+where
+    P: Borrow<PopupState> + BorrowMut<PopupState>,
+    S: Borrow<Selection> + BorrowMut<Selection>,
+    T: theme::Theme<'a>,
+{
+    type Output = util::RectIter<T::Color>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        // The background hides the widgets below the list
+        let theme = context.theme;
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        egrectangle!(
+            (x1, y1),
+            (x2, y2),
+            fill = Some(theme.background_color()),
+            stroke = Some(theme.border_color())
+        )
+        .into_iter()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        let padding = stretch::style::Dimension::Points(1.0);
+        layout::Style {
+            position_type: stretch::style::PositionType::Absolute,
+            position: stretch::geometry::Rect {
+                start: stretch::style::Dimension::Points(0.0),
+                top: stretch::style::Dimension::Percent(1.0),
+                ..Default::default()
+            },
+            min_size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Percent(1.0),
+                height: stretch::style::Dimension::Auto,
+            },
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_items: stretch::style::AlignItems::Stretch,
+            margin: Default::default(),
+            padding: stretch::geometry::Rect {
+                start: padding,
+                end: padding,
+                top: padding,
+                bottom: padding,
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.borrow().layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.borrow_mut().layout
+    }
+
+    fn layer(&self) -> u8 {
+        POPUP_LAYER
+    }
+
+    fn is_modal(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let pointer = match (context.phase, event) {
+            (event::Phase::Target, event::Event::Pointer(pointer)) => pointer,
+            _ => return false,
+        };
+
+        // Presses outside of the list are delivered here because it is modal, and close it
+        if pointer.action == event::PointerAction::Down && !context.contains(pointer.position) {
+            let selection = self.selection.borrow_mut();
+            selection.open = false;
+            selection.pressed = None;
+        }
+        true
+    }
+}
+
+impl<'a, L, S, T, M> widget::Node<'a, T, M> for Item<'a, L, S, M>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    S: Borrow<Selection> + BorrowMut<Selection>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, L, S, T, M> widget::Widget<'a, T, M> for Item<'a, L, S, M>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    S: Borrow<Selection> + BorrowMut<Selection>,
+    T: theme::Theme<'a>,
+{
+    type Output =
+        core::iter::Chain<util::OptionalIter<util::RectIter<T::Color>>, util::FontIter<'a, T>>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let theme = context.theme;
+        let highlighted = self.selection.borrow().highlighted == self.index;
+        let (fill, text_color) = if highlighted {
+            (Some(theme.fill_color()), theme.background_color())
+        } else if context.hovered {
+            (Some(theme.hover_color()), theme.text_color())
+        } else {
+            (None, theme.text_color())
+        };
+
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let rect_iter =
+            fill.map(|fill| egrectangle!((x1, y1), (x2, y2), fill = Some(fill)).into_iter());
+
+        let text = T::Font::render_str(self.label);
+        let xt = x1 + context.theme.spacing() as i32;
+        let yt = y1 + (context.size.height as i32 - text.size().height as i32) / 2;
+        let text_iter = text
+            .translate(embedded_graphics::geometry::Point::new(xt, yt))
+            .stroke(Some(text_color))
+            .into_iter();
+
+        rect_iter.into_iter().flatten().chain(text_iter)
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        // The list stretches the options to its width, which is at least that of the box
+        let height = T::Font::render_str(self.label).size().height as f32;
+        layout::Style {
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height: stretch::style::Dimension::Points(height + 2.0 * theme.spacing()),
+            },
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        self.layout.borrow()
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        self.layout.borrow_mut()
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let pointer = match event {
+            event::Event::Pointer(pointer) => pointer,
+            _ => return false,
+        };
+
+        let index = self.index;
+        let selection = self.selection.borrow_mut();
+        match pointer.action {
+            event::PointerAction::Down => {
+                selection.pressed = Some(index);
+                selection.highlighted = index;
+                true
+            }
+            event::PointerAction::Up => {
+                if selection.pressed == Some(index) && context.contains(pointer.position) {
+                    selection.choose(context, index, self.on_select);
+                }
+                selection.pressed = None;
+                true
+            }
+            event::PointerAction::Move => false,
+        }
+    }
+}
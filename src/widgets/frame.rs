@@ -10,16 +10,16 @@ pub struct State {
 }
 
 #[derive(Debug)]
-pub struct Frame<'a, N, T> {
+pub struct Frame<'a, N, T, M> {
     state: &'a mut State,
-    phantom: marker::PhantomData<T>,
+    phantom: marker::PhantomData<(T, M)>,
     label: Option<&'a str>,
     child: N,
 }
 
-impl<'a, N, T> Frame<'a, N, T>
+impl<'a, N, T, M> Frame<'a, N, T, M>
 where
-    N: widget::Node<'a, T>,
+    N: widget::Node<'a, T, M>,
     T: theme::Theme<'a>,
 {
     pub fn new(state: &'a mut State, label: &'a str, child: N) -> Self {
@@ -34,14 +34,14 @@ where
     }
 }
 
-impl<'a, N, T> widget::Node<'a, T> for Frame<'a, N, T>
+impl<'a, N, T, M> widget::Node<'a, T, M> for Frame<'a, N, T, M>
 where
-    N: widget::Node<'a, T>,
+    N: widget::Node<'a, T, M>,
     T: theme::Theme<'a>,
 {
     fn visit<V>(&self, visitor: V) -> V::Output
     where
-        V: widget::Visitor<'a, T>,
+        V: widget::Visitor<'a, T, M>,
     {
         use widget::ChildVisitor;
 
@@ -52,7 +52,7 @@ where
 
     fn visit_mut<V>(&mut self, visitor: V) -> V::Output
     where
-        V: widget::MutVisitor<'a, T>,
+        V: widget::MutVisitor<'a, T, M>,
     {
         use widget::MutChildVisitor;
 
@@ -62,9 +62,9 @@ where
     }
 }
 
-impl<'a, N, T> widget::Widget<'a, T> for Frame<'a, N, T>
+impl<'a, N, T, M> widget::Widget<'a, T, M> for Frame<'a, N, T, M>
 where
-    N: widget::Node<'a, T>,
+    N: widget::Node<'a, T, M>,
     T: theme::Theme<'a>,
 {
     type Output =
@@ -0,0 +1,248 @@
+use crate::layout;
+use crate::theme;
+use crate::widget;
+use embedded_graphics::drawable;
+use embedded_graphics::geometry;
+
+/// The state of an image widget.
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+}
+
+/// How an image is scaled to the box it is laid out in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scale {
+    /// Drawn at its own size, and cut off if the box is smaller.
+    None,
+    /// As large as fits in the box, keeping its aspect ratio.
+    Fit,
+    /// Like `Fit`, but only by whole multiples of its own size so that pixels stay square; it is
+    /// drawn at its own size and cut off if it does not fit.
+    Integer,
+}
+
+/// How an image is aligned within its box, horizontally or vertically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+/// Shows an image, e.g. a logo or an icon.
+///
+/// Any of the `embedded-graphics` image types can be shown, such as `Image1BPP` and `Image16BPP`
+/// for raw image data, or `ImageBmp` and `ImageTga` with the `bmp` and `tga` features of
+/// `embedded-graphics` enabled.  The widget asks the layout for the size of the image, and when it
+/// is scaled, it also grows with the space that is available, in both directions.
+pub struct Image<'a, I> {
+    state: &'a mut State,
+    image: &'a I,
+    scale: Scale,
+    horizontal: Align,
+    vertical: Align,
+}
+
+/// The pixels of a scaled image, clipped to its box.
+pub struct Pixels<P, C> {
+    source: P,
+    origin: geometry::Point,
+    position: geometry::Point,
+    top_left: geometry::Point,
+    bottom_right: geometry::Point,
+    numerator: i32,
+    denominator: i32,
+    block: Option<Block<C>>,
+}
+
+/// The pixels that a single pixel of the source image is scaled to.
+struct Block<C> {
+    color: C,
+    x1: i32,
+    x2: i32,
+    y2: i32,
+    x: i32,
+    y: i32,
+}
+
+impl<'a, I> Image<'a, I> {
+    /// Creates an image widget showing `image` at its own size, centered.
+    pub fn new(state: &'a mut State, image: &'a I) -> Self {
+        let scale = Scale::None;
+        let horizontal = Align::Center;
+        let vertical = Align::Center;
+        Self {
+            state,
+            image,
+            scale,
+            horizontal,
+            vertical,
+        }
+    }
+
+    /// Sets how the image is scaled to its box; defaults to `Scale::None`.
+    pub fn scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets how the image is aligned within its box if it doesn't fill it; defaults to centered.
+    pub fn align(mut self, horizontal: Align, vertical: Align) -> Self {
+        self.horizontal = horizontal;
+        self.vertical = vertical;
+        self
+    }
+}
+
+impl<'a, I, T, M> widget::Node<'a, T, M> for Image<'a, I>
+where
+    I: geometry::Dimensions,
+    &'a I: IntoIterator<Item = drawable::Pixel<T::Color>>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, I, T, M> widget::Widget<'a, T, M> for Image<'a, I>
+where
+    I: geometry::Dimensions,
+    &'a I: IntoIterator<Item = drawable::Pixel<T::Color>>,
+    T: theme::Theme<'a>,
+{
+    type Output = Pixels<<&'a I as IntoIterator>::IntoIter, T::Color>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let image = self.image.size();
+        let (image_width, image_height) = (image.width as i32, image.height as i32);
+        let (width, height) = (context.size.width as i32, context.size.height as i32);
+
+        let (numerator, denominator) = match self.scale {
+            _ if image_width == 0 || image_height == 0 => (1, 1),
+            Scale::None => (1, 1),
+            Scale::Fit if width * image_height <= height * image_width => (width, image_width),
+            Scale::Fit => (height, image_height),
+            Scale::Integer => ((width / image_width).min(height / image_height).max(1), 1),
+        };
+
+        let x = align(
+            self.horizontal,
+            width,
+            image_width * numerator / denominator,
+        );
+        let y = align(
+            self.vertical,
+            height,
+            image_height * numerator / denominator,
+        );
+        Pixels {
+            source: self.image.into_iter(),
+            origin: self.image.top_left(),
+            position: context.position + geometry::Point::new(x, y),
+            top_left: context.position,
+            bottom_right: context.position + geometry::Point::new(width, height),
+            numerator,
+            denominator,
+            block: None,
+        }
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        let size = self.image.size();
+        let size = stretch::geometry::Size {
+            width: stretch::style::Dimension::Points(size.width as f32),
+            height: stretch::style::Dimension::Points(size.height as f32),
+        };
+        if self.scale == Scale::None {
+            return layout::Style {
+                flex_shrink: 0.0,
+                size,
+                ..theme.base_style()
+            };
+        }
+
+        // Scaled images are at least as large as the image, and grow along both axes
+        layout::Style {
+            flex_grow: 1.0,
+            flex_shrink: 0.0,
+            align_self: stretch::style::AlignSelf::Stretch,
+            min_size: size,
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+}
+
+impl<P, C> Iterator for Pixels<P, C>
+where
+    P: Iterator<Item = drawable::Pixel<C>>,
+    C: embedded_graphics::pixelcolor::PixelColor,
+{
+    type Item = drawable::Pixel<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(block) = &mut self.block {
+                if block.y < block.y2 {
+                    let point = geometry::Point::new(block.x, block.y);
+                    block.x += 1;
+                    if block.x >= block.x2 {
+                        block.x = block.x1;
+                        block.y += 1;
+                    }
+                    return Some(drawable::Pixel(point, block.color));
+                }
+            }
+
+            // Every source pixel covers the pixels from where it starts to where the next one does,
+            // which are none for some of them when the image is scaled down
+            let drawable::Pixel(point, color) = self.source.next()?;
+            let point = point - self.origin;
+            let scale = |i: i32| i * self.numerator / self.denominator;
+            let x1 = (self.position.x + scale(point.x)).max(self.top_left.x);
+            let y1 = (self.position.y + scale(point.y)).max(self.top_left.y);
+            let x2 = (self.position.x + scale(point.x + 1)).min(self.bottom_right.x);
+            let y2 = (self.position.y + scale(point.y + 1)).min(self.bottom_right.y);
+            self.block = if x1 < x2 && y1 < y2 {
+                Some(Block {
+                    color,
+                    x1,
+                    x2,
+                    y2,
+                    x: x1,
+                    y: y1,
+                })
+            } else {
+                None
+            };
+        }
+    }
+}
+
+/// The offset of something of `size` aligned within `available` pixels.
+fn align(align: Align, available: i32, size: i32) -> i32 {
+    match align {
+        Align::Start => 0,
+        Align::Center => (available - size) / 2,
+        Align::End => available - size,
+    }
+}
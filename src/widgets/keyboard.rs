@@ -0,0 +1,552 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::borrow::Borrow;
+use core::borrow::BorrowMut;
+use embedded_graphics::egrectangle;
+
+/// The maximum number of keys in a row of any page.
+pub type MaxKeys = heapless::consts::U10;
+
+const ROWS: usize = 4;
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    rows: [RowState; ROWS],
+    keys: Keys,
+}
+
+#[derive(Clone, Debug, Default)]
+struct RowState {
+    layout: layout::State,
+    keys: heapless::Vec<layout::State, MaxKeys>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Keys {
+    page: Page,
+    shift: Shift,
+    pressed: Option<(usize, usize)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Shift,
+    Backspace,
+    Space,
+    Enter,
+    /// Switches to another page of keys.
+    Page(Page),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Page {
+    Letters,
+    Numbers,
+    Symbols,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shift {
+    Off,
+    /// Only the next letter is upper case.
+    Once,
+    /// All letters are upper case until shift is pressed again, i.e. caps lock.
+    Locked,
+}
+
+/// A run of keys in a row of a page.
+enum Run {
+    Chars(&'static str),
+    Key(Key),
+}
+
+const LETTERS: [&[Run]; ROWS] = [
+    &[Run::Chars("qwertyuiop")],
+    &[Run::Chars("asdfghjkl")],
+    &[
+        Run::Key(Key::Shift),
+        Run::Chars("zxcvbnm"),
+        Run::Key(Key::Backspace),
+    ],
+    &[
+        Run::Key(Key::Page(Page::Numbers)),
+        Run::Key(Key::Space),
+        Run::Key(Key::Enter),
+    ],
+];
+
+const NUMBERS: [&[Run]; ROWS] = [
+    &[Run::Chars("1234567890")],
+    &[Run::Chars("-/:;()$&@\"")],
+    &[
+        Run::Key(Key::Page(Page::Symbols)),
+        Run::Chars(".,?!'"),
+        Run::Key(Key::Backspace),
+    ],
+    &[
+        Run::Key(Key::Page(Page::Letters)),
+        Run::Key(Key::Space),
+        Run::Key(Key::Enter),
+    ],
+];
+
+const SYMBOLS: [&[Run]; ROWS] = [
+    &[Run::Chars("[]{}#%^*+=")],
+    &[Run::Chars("_\\|~<>`")],
+    &[
+        Run::Key(Key::Page(Page::Numbers)),
+        Run::Chars(".,?!'"),
+        Run::Key(Key::Backspace),
+    ],
+    &[
+        Run::Key(Key::Page(Page::Letters)),
+        Run::Key(Key::Space),
+        Run::Key(Key::Enter),
+    ],
+];
+
+/// An on-screen keyboard for touch panels, with pages of letters, numbers and symbols.
+///
+/// The keys type into the focused widget, e.g. a `text_input::TextInput`, by posting `Edit`
+/// events, and `Enter` posts `Activate`.  The keyboard itself can't be focused, so tapping it
+/// leaves the focus where it is.  Shift makes the next letter upper case, and pressing it twice
+/// locks it.
+///
+/// The rows are laid out with flexbox to fill the width of the keyboard, so it fits on displays as
+/// small as 160 pixels wide with a 6x8 font.
+#[derive(Debug)]
+pub struct Keyboard<'a, M> {
+    state: &'a mut State,
+    on_key: Option<fn(Key) -> M>,
+}
+
+/// A row of keys, which borrows its state from the keyboard's `State` while it is visited, or
+/// holds copies of it if the visitor doesn't need to modify it.
+struct KeyRow<R, K, M> {
+    state: R,
+    keys: K,
+    row: usize,
+    on_key: Option<fn(Key) -> M>,
+}
+
+/// A single key, like a row.
+struct KeyButton<L, K, M> {
+    layout: L,
+    keys: K,
+    key: Key,
+    position: (usize, usize),
+    on_key: Option<fn(Key) -> M>,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Page::Letters
+    }
+}
+
+impl Default for Shift {
+    fn default() -> Self {
+        Shift::Off
+    }
+}
+
+impl State {
+    pub fn page(&self) -> Page {
+        self.keys.page
+    }
+
+    pub fn set_page(&mut self, page: Page) {
+        self.keys.page = page;
+        self.keys.pressed = None;
+    }
+
+    pub fn shift(&self) -> Shift {
+        self.keys.shift
+    }
+}
+
+impl Keys {
+    /// Applies `key` to the keyboard, and returns the event it types, if any.
+    fn press(&mut self, key: Key) -> Option<event::Event> {
+        match key {
+            Key::Char(c) => {
+                let c = match self.shift {
+                    Shift::Off => c,
+                    Shift::Once => {
+                        self.shift = Shift::Off;
+                        c.to_ascii_uppercase()
+                    }
+                    Shift::Locked => c.to_ascii_uppercase(),
+                };
+                Some(event::Edit::Insert(c).into())
+            }
+            Key::Shift => {
+                self.shift = match self.shift {
+                    Shift::Off => Shift::Once,
+                    Shift::Once => Shift::Locked,
+                    Shift::Locked => Shift::Off,
+                };
+                None
+            }
+            Key::Backspace => Some(event::Edit::Backspace.into()),
+            Key::Space => Some(event::Edit::Insert(' ').into()),
+            Key::Enter => Some(event::Navigation::Activate.into()),
+            Key::Page(page) => {
+                self.page = page;
+                self.shift = Shift::Off;
+                None
+            }
+        }
+    }
+}
+
+impl Key {
+    fn label(self, shift: Shift) -> &'static str {
+        match self {
+            Key::Char(c) if shift != Shift::Off => util::glyph(c.to_ascii_uppercase() as u8),
+            Key::Char(c) => util::glyph(c as u8),
+            Key::Shift => "^",
+            Key::Backspace => "<",
+            Key::Space => "",
+            Key::Enter => "OK",
+            Key::Page(Page::Letters) => "abc",
+            Key::Page(Page::Numbers) => "123",
+            Key::Page(Page::Symbols) => "#+=",
+        }
+    }
+
+    /// How wide the key is relative to a character key.
+    fn width(self) -> f32 {
+        match self {
+            Key::Char(_) => 1.0,
+            Key::Space => 5.0,
+            _ => 1.5,
+        }
+    }
+}
+
+impl Page {
+    /// The keys in row `row` of this page.
+    fn keys(self, row: usize) -> impl Iterator<Item = Key> {
+        let rows = match self {
+            Page::Letters => &LETTERS,
+            Page::Numbers => &NUMBERS,
+            Page::Symbols => &SYMBOLS,
+        };
+        rows[row].iter().flat_map(|run| {
+            let (chars, key) = match *run {
+                Run::Chars(chars) => (chars, None),
+                Run::Key(key) => ("", Some(key)),
+            };
+            chars.chars().map(Key::Char).chain(key)
+        })
+    }
+}
+
+impl<'a, M> Keyboard<'a, M> {
+    pub fn new(state: &'a mut State) -> Self {
+        let on_key = None;
+        Self { state, on_key }
+    }
+
+    /// Sets the function that creates the message emitted with every key that is pressed, in
+    /// addition to the events that it posts.
+    pub fn on_key(mut self, on_key: fn(Key) -> M) -> Self {
+        self.on_key = Some(on_key);
+        self
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Keyboard<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, ROWS);
+        for (row, state) in self.state.rows.iter().enumerate() {
+            visitor.accept_child(&KeyRow {
+                state: state.clone(),
+                keys: self.state.keys,
+                row,
+                on_key: self.on_key,
+            });
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let on_key = self.on_key;
+        let mut visitor = visitor.accept_node_mut(self, ROWS);
+        let State { rows, keys, .. } = &mut *self.state;
+        for (row, state) in rows.iter_mut().enumerate() {
+            visitor.accept_child_mut(&mut KeyRow {
+                state,
+                keys: &mut *keys,
+                row,
+                on_key,
+            });
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Keyboard<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let glyph_size = T::Font::render_str("0").size();
+        let height = ROWS as f32 * (glyph_size.height as f32 + 2.0 * theme.spacing());
+        layout::Style {
+            flex_shrink: 0.0,
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_self: stretch::style::AlignSelf::Stretch,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height: stretch::style::Dimension::Points(height),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+}
+
+impl<'a, R, K, T, M> widget::Node<'a, T, M> for KeyRow<R, K, M>
+where
+    R: Borrow<RowState> + BorrowMut<RowState>,
+    K: Borrow<Keys> + BorrowMut<Keys>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let keys = *self.keys.borrow();
+        let state = self.state.borrow();
+        let count = keys.page.keys(self.row).count();
+        let mut visitor = visitor.accept_node(self, count);
+        for (index, key) in keys.page.keys(self.row).enumerate() {
+            visitor.accept_child(&KeyButton {
+                layout: state.keys.get(index).cloned().unwrap_or_default(),
+                keys,
+                key,
+                position: (self.row, index),
+                on_key: self.on_key,
+            });
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let row = self.row;
+        let on_key = self.on_key;
+        let page = self.keys.borrow().page;
+        let count = page.keys(row).count();
+        let _ = self.state.borrow_mut().keys.resize_default(count);
+
+        let mut visitor = visitor.accept_node_mut(self, count);
+        let layouts = &mut self.state.borrow_mut().keys;
+        let keys = self.keys.borrow_mut();
+        for (index, (layout, key)) in layouts.iter_mut().zip(page.keys(row)).enumerate() {
+            visitor.accept_child_mut(&mut KeyButton {
+                layout,
+                keys: &mut *keys,
+                key,
+                position: (row, index),
+                on_key,
+            });
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, R, K, T, M> widget::Widget<'a, T, M> for KeyRow<R, K, M>
+where
+    R: Borrow<RowState> + BorrowMut<RowState>,
+    K: Borrow<Keys> + BorrowMut<Keys>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        layout::Style {
+            flex_grow: 1.0,
+            flex_shrink: 1.0,
+            flex_basis: stretch::style::Dimension::Points(0.0),
+            flex_direction: stretch::style::FlexDirection::Row,
+            align_items: stretch::style::AlignItems::Stretch,
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.borrow().layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.borrow_mut().layout
+    }
+}
+
+impl<'a, L, K, T, M> widget::Node<'a, T, M> for KeyButton<L, K, M>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    K: Borrow<Keys> + BorrowMut<Keys>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, L, K, T, M> widget::Widget<'a, T, M> for KeyButton<L, K, M>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    K: Borrow<Keys> + BorrowMut<Keys>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<util::RectIter<T::Color>, util::FontIter<'a, T>>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let theme = context.theme;
+        let keys = self.keys.borrow();
+        let pressed = keys.pressed == Some(self.position);
+        let (fill, text_color) = match (self.key, keys.shift) {
+            _ if pressed => (Some(theme.pressed_color()), theme.background_color()),
+            (Key::Shift, Shift::Locked) => (Some(theme.fill_color()), theme.background_color()),
+            (Key::Shift, Shift::Once) => (Some(theme.hover_color()), theme.text_color()),
+            _ if context.hovered => (Some(theme.hover_color()), theme.text_color()),
+            _ => (None, theme.text_color()),
+        };
+
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let rect = egrectangle!(
+            (x1, y1),
+            (x2, y2),
+            fill = fill,
+            stroke = Some(theme.border_color())
+        );
+
+        let text = T::Font::render_str(self.key.label(keys.shift));
+        let text_size = text.size();
+        let xt = x1 + (context.size.width as i32 - text_size.width as i32) / 2;
+        let yt = y1 + (context.size.height as i32 - text_size.height as i32) / 2;
+        let text = text
+            .translate(embedded_graphics::geometry::Point::new(xt, yt))
+            .stroke(Some(text_color));
+
+        rect.into_iter().chain(text.into_iter())
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        // Keys are packed without margins, so that a full row fits on small displays
+        layout::Style {
+            flex_grow: self.key.width(),
+            flex_shrink: 1.0,
+            flex_basis: stretch::style::Dimension::Points(0.0),
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        self.layout.borrow()
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        self.layout.borrow_mut()
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let pointer = match event {
+            event::Event::Pointer(pointer) => pointer,
+            _ => return false,
+        };
+
+        let keys = self.keys.borrow_mut();
+        match pointer.action {
+            event::PointerAction::Down => {
+                keys.pressed = Some(self.position);
+                true
+            }
+            event::PointerAction::Up => {
+                if keys.pressed == Some(self.position) && context.contains(pointer.position) {
+                    if let Some(event) = keys.press(self.key) {
+                        context.post(event);
+                    }
+                    if let Some(on_key) = self.on_key {
+                        context.emit(on_key(self.key));
+                    }
+                }
+                keys.pressed = None;
+                true
+            }
+            event::PointerAction::Move => false,
+        }
+    }
+}
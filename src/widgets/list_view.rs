@@ -0,0 +1,356 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use crate::widgets::rows;
+use embedded_graphics::egrectangle;
+
+/// The maximum number of rows that a list view shows at once.
+pub type MaxRows = heapless::consts::U16;
+
+/// The state of a list view, including the states of the widgets in its visible rows.
+///
+/// The states of the row widgets belong to the rows rather than to the items, so when the list is
+/// scrolled, the row that shows an item reuses the state of the item that was there before.
+#[derive(Debug, Default)]
+pub struct State<S> {
+    rows: heapless::Vec<RowState<S>, MaxRows>,
+    view: View,
+}
+
+#[derive(Debug, Default)]
+struct RowState<S> {
+    layout: layout::State,
+    item: S,
+}
+
+#[derive(Debug, Default)]
+struct View {
+    layout: layout::State,
+    rows: rows::Rows,
+    /// The item to scroll to once the number of items and visible rows is known.
+    scroll_to: Option<usize>,
+}
+
+/// A scrollable list of items, of which only the visible ones are built, laid out and drawn.
+///
+/// The widget for an item is built by a closure from its index and the state of its row, so lists
+/// can have far more items than would fit in memory as widgets.  All rows are equally high, and
+/// the list is as high as the number of visible rows it is created with.
+///
+/// The list scrolls by whole rows when the pointer is dragged over it, and tapping an item selects
+/// it.  While it has the focus, the up and down arrows move a highlight through the items, and
+/// scroll the list to keep it visible.  `Activate` enters an editing mode in which `Next` and
+/// `Previous` move the highlight too, and selects the highlighted item when it leaves that mode
+/// again; see `event::Navigation`.
+#[derive(Debug)]
+pub struct ListView<'a, W, M> {
+    view: &'a mut View,
+    rows: heapless::Vec<Row<'a, W>, MaxRows>,
+    on_select: Option<fn(usize) -> M>,
+}
+
+/// A row of the list, which is as high as all other rows.
+#[derive(Debug)]
+struct Row<'a, W> {
+    layout: &'a mut layout::State,
+    widget: W,
+}
+
+impl<S> State<S> {
+    /// The index of the first visible item.
+    pub fn first(&self) -> usize {
+        self.view.rows.first
+    }
+
+    /// The index of the highlighted item, if any.
+    pub fn highlighted(&self) -> Option<usize> {
+        self.view.rows.highlighted
+    }
+
+    /// Scrolls the list as little as possible to make the item at `index` visible, when the list
+    /// view is next created.
+    pub fn scroll_to(&mut self, index: usize) {
+        self.view.scroll_to = Some(index);
+    }
+}
+
+impl View {
+    /// The height of a row of a list view with size `size`.
+    fn row_height(&self, size: embedded_graphics::geometry::Size) -> i32 {
+        (size.height as i32 - 2).max(0) / self.rows.visible.max(1) as i32
+    }
+}
+
+impl<'a, W, M> ListView<'a, W, M> {
+    /// Creates a list of `len` items that shows `rows` of them at once, at most `MaxRows`.
+    ///
+    /// `item` is called with the index and row state of every visible item to build its widget.
+    pub fn new<S, F>(state: &'a mut State<S>, len: usize, rows: usize, mut item: F) -> Self
+    where
+        S: Default,
+        F: FnMut(usize, &'a mut S) -> W,
+    {
+        let State {
+            rows: row_states,
+            view,
+        } = state;
+        view.rows.visible = rows.max(1).min(row_states.capacity());
+        view.rows.set_len(len);
+        if let Some(index) = view.scroll_to.take() {
+            view.rows.scroll_to(index);
+        }
+
+        let count = view.rows.range().len();
+        while row_states.len() < count {
+            let _ = row_states.push(RowState::default());
+        }
+        let first = view.rows.first;
+        let rows = row_states
+            .iter_mut()
+            .take(count)
+            .enumerate()
+            .map(|(i, row)| Row {
+                layout: &mut row.layout,
+                widget: item(first + i, &mut row.item),
+            })
+            .collect();
+
+        let on_select = None;
+        Self {
+            view,
+            rows,
+            on_select,
+        }
+    }
+
+    /// Sets the function that creates the message emitted with the index of an item when it is
+    /// selected.
+    pub fn on_select(mut self, on_select: fn(usize) -> M) -> Self {
+        self.on_select = Some(on_select);
+        self
+    }
+
+    fn select<T>(&mut self, context: &mut widget::EventContext<T, M>, index: usize) {
+        self.view.rows.highlighted = Some(index);
+        if let Some(on_select) = self.on_select {
+            context.emit(on_select(index));
+        }
+    }
+}
+
+impl<'a, W, T, M> widget::Node<'a, T, M> for ListView<'a, W, M>
+where
+    W: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, self.rows.len());
+        for row in &self.rows {
+            visitor.accept_child(row);
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let mut visitor = visitor.accept_node_mut(self, self.rows.len());
+        for row in &mut self.rows {
+            visitor.accept_child_mut(row);
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, W, T, M> widget::Widget<'a, T, M> for ListView<'a, W, M>
+where
+    W: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<util::RectIter<T::Color>, util::OptionalIter<util::RectIter<T::Color>>>,
+        util::OptionalIter<util::RectIter<T::Color>>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let theme = context.theme;
+        let state = &self.view.rows;
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let row_height = self.view.row_height(context.size);
+        let scrollbar_width = theme.scrollbar_width() as i32;
+
+        let border = egrectangle!((x1, y1), (x2, y2), stroke = Some(theme.border_color()));
+
+        // The highlight is an outline, so that it doesn't hide the row, also on monochrome displays
+        let highlight = state
+            .highlighted
+            .filter(|index| state.range().contains(index))
+            .map(|index| {
+                let color = if state.editing && context.focused {
+                    theme.focus_color()
+                } else {
+                    theme.fill_color()
+                };
+                let y = y1 + 1 + (index - state.first) as i32 * row_height;
+                egrectangle!(
+                    (x1 + 1, y),
+                    (x2 - scrollbar_width - 1, y + row_height - 1),
+                    stroke = Some(color)
+                )
+                .into_iter()
+            });
+
+        let scrollbar = state.scrollbar(x2, y1 + 1, y2, scrollbar_width, theme.fill_color());
+
+        border
+            .into_iter()
+            .chain(highlight.into_iter().flatten())
+            .chain(scrollbar.into_iter().flatten())
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let row_height = T::Font::render_str("0").size().height as f32 + 2.0 * theme.spacing();
+        let height = self.view.rows.visible as f32 * row_height + 2.0;
+        let border = stretch::style::Dimension::Points(1.0);
+
+        layout::Style {
+            flex_shrink: 0.0,
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_self: stretch::style::AlignSelf::Stretch,
+            align_items: stretch::style::AlignItems::Stretch,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height: stretch::style::Dimension::Points(height),
+            },
+            padding: stretch::geometry::Rect {
+                start: border,
+                end: stretch::style::Dimension::Points(theme.scrollbar_width() + 1.0),
+                top: border,
+                bottom: border,
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.view.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.view.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.view.rows.len > 0
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let row_height = self.view.row_height(context.size);
+        let top = context.position.y + 1;
+        let state = &mut self.view.rows;
+        let outcome = match (context.phase, event) {
+            // Rows that don't handle the pointer themselves let it bubble up to the list
+            (event::Phase::Capture, _) => rows::Outcome::Ignored,
+            (_, event::Event::Pointer(pointer)) => state.drag(context, pointer, top, row_height),
+            (event::Phase::Target, event::Event::Navigation(navigation)) => {
+                state.navigate(*navigation)
+            }
+            _ => rows::Outcome::Ignored,
+        };
+
+        match outcome {
+            rows::Outcome::Ignored => false,
+            rows::Outcome::Handled => true,
+            rows::Outcome::Chosen(index) => {
+                self.select(context, index);
+                true
+            }
+        }
+    }
+}
+
+impl<'a, W, T, M> widget::Node<'a, T, M> for Row<'a, W>
+where
+    W: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, 1);
+        visitor.accept_child(&self.widget);
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let mut visitor = visitor.accept_node_mut(self, 1);
+        visitor.accept_child_mut(&mut self.widget);
+        visitor.end()
+    }
+}
+
+impl<'a, W, T, M> widget::Widget<'a, T, M> for Row<'a, W>
+where
+    W: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let height = T::Font::render_str("0").size().height as f32 + 2.0 * theme.spacing();
+        layout::Style {
+            flex_shrink: 0.0,
+            flex_direction: stretch::style::FlexDirection::Row,
+            align_items: stretch::style::AlignItems::Center,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height: stretch::style::Dimension::Points(height),
+            },
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+}
@@ -0,0 +1,756 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::fmt::Write;
+use embedded_graphics::egrectangle;
+
+/// The maximum depth of submenus that a menu can enter.
+pub type MaxDepth = heapless::consts::U8;
+
+type TitleCapacity = heapless::consts::U64;
+type DetailCapacity = heapless::consts::U16;
+
+const SEPARATOR: &str = " > ";
+const ELLIPSIS: &str = "..";
+const BACK: &str = "..";
+
+/// An entry of a menu, identified by an `A` when it is chosen.
+///
+/// Menus are trees of entries that can be built in `const` items, e.g.
+/// `const MENU: Entry<Id> = Entry::submenu("Main", &[Entry::action("Start", Id::Start)]);`.
+#[derive(Debug)]
+pub enum Entry<'a, A> {
+    /// An entry that opens a menu of its own.
+    Submenu {
+        label: &'a str,
+        entries: &'a [Entry<'a, A>],
+    },
+    /// An entry that performs an action.
+    Action { label: &'a str, action: A },
+    /// An entry that switches a setting on and off, showing whether it is on.
+    Toggle { label: &'a str, toggle: A },
+    /// An entry that edits a number in steps of `step` from `min` to `max`, showing the number.
+    Value {
+        label: &'a str,
+        value: A,
+        min: i32,
+        max: i32,
+        step: i32,
+    },
+}
+
+/// The settings shown by the toggle and value entries of a menu, as kept by the application.
+pub trait Values<A> {
+    fn is_on(&self, toggle: A) -> bool;
+
+    fn value(&self, value: A) -> i32;
+}
+
+/// What was chosen in a menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Choice<A> {
+    /// An action entry was activated.
+    Action(A),
+    /// A toggle entry was activated, and should be switched to the given setting.
+    Toggle(A, bool),
+    /// A value entry was edited, and should be set to the given number.
+    Value(A, i32),
+}
+
+/// The state of a menu, including which submenu it is in.
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    path: heapless::Vec<usize, MaxDepth>,
+    highlighted: usize,
+    first: usize,
+    visible: usize,
+    editing: Option<i32>,
+    pressed: Option<usize>,
+}
+
+/// A hierarchical menu that shows the entries of one submenu at a time.
+///
+/// The title shows the path of submenus from the root, cut off at the start if it doesn't fit.
+/// Every submenu starts with a row that goes back to its parent, as does tapping the title or the
+/// `Back` event; that row is the only way back on devices without a touch panel or a button that
+/// emits `Back`, such as a rotary encoder.  Tapping a row or `Activate` activates the highlighted
+/// entry: submenus are entered, and for actions and toggles a message is emitted right away.
+/// Value entries enter an editing mode in which `Next` and `Previous` or the arrows change the
+/// number, emitting a message for every step, until `Activate` or `Back` leaves it again.  On touch
+/// panels, tapping the left or right half of the edited row steps the number down or up, and a
+/// tap anywhere else leaves the editing mode before it is handled as usual.
+///
+/// The menu grows to fill the space available in its parent, and scrolls to keep the highlighted
+/// entry visible.
+#[derive(Debug)]
+pub struct Menu<'a, A, V, M> {
+    state: &'a mut State,
+    root: &'a Entry<'a, A>,
+    entries: &'a [Entry<'a, A>],
+    values: &'a V,
+    on_choice: Option<fn(Choice<A>) -> M>,
+}
+
+/// The parts of the visible rows of a menu.
+pub struct Rows<'a, T, A, V>
+where
+    T: theme::Theme<'a>,
+{
+    entries: &'a [Entry<'a, A>],
+    values: &'a V,
+    back: bool,
+    rows: core::ops::Range<usize>,
+    highlighted: usize,
+    editing: Option<i32>,
+    grid: Grid,
+    text_color: T::Color,
+    highlight_color: T::Color,
+    highlight_text_color: T::Color,
+    row: usize,
+    step: usize,
+}
+
+/// The dimensions of the rows of a menu, in pixels.
+#[derive(Clone, Copy, Debug)]
+struct Grid {
+    x1: i32,
+    x2: i32,
+    y1: i32,
+    row_height: i32,
+    padding: i32,
+    advance: i32,
+}
+
+/// A row of a menu.
+#[derive(Clone, Copy)]
+enum Row<'a, A> {
+    Back,
+    Entry(&'a Entry<'a, A>),
+}
+
+impl<'a, A> Entry<'a, A> {
+    pub const fn submenu(label: &'a str, entries: &'a [Entry<'a, A>]) -> Self {
+        Entry::Submenu { label, entries }
+    }
+
+    pub const fn action(label: &'a str, action: A) -> Self {
+        Entry::Action { label, action }
+    }
+
+    pub const fn toggle(label: &'a str, toggle: A) -> Self {
+        Entry::Toggle { label, toggle }
+    }
+
+    pub const fn value(label: &'a str, value: A, min: i32, max: i32, step: i32) -> Self {
+        Entry::Value {
+            label,
+            value,
+            min,
+            max,
+            step,
+        }
+    }
+
+    pub fn label(&self) -> &'a str {
+        match *self {
+            Entry::Submenu { label, .. }
+            | Entry::Action { label, .. }
+            | Entry::Toggle { label, .. }
+            | Entry::Value { label, .. } => label,
+        }
+    }
+
+    /// The entries of a submenu, or none for other entries.
+    fn entries(&self) -> &'a [Entry<'a, A>] {
+        match *self {
+            Entry::Submenu { entries, .. } => entries,
+            _ => &[],
+        }
+    }
+}
+
+impl State {
+    /// How many submenus deep the menu is.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Goes back to the root of the menu.
+    pub fn reset(&mut self) {
+        self.path.clear();
+        self.highlighted = 0;
+        self.first = 0;
+        self.editing = None;
+    }
+
+    /// The number of rows of the current submenu, including the row that goes back.
+    fn row_count(&self, entries: usize) -> usize {
+        if self.path.is_empty() {
+            entries
+        } else {
+            entries + 1
+        }
+    }
+
+    /// Scrolls as little as possible to make the highlighted row visible.
+    fn scroll_to_highlight(&mut self, rows: usize) {
+        if self.highlighted < self.first {
+            self.first = self.highlighted;
+        } else if self.highlighted >= self.first + self.visible {
+            self.first = self.highlighted + 1 - self.visible.max(1);
+        }
+        self.first = self.first.min(rows.saturating_sub(self.visible));
+    }
+}
+
+impl<'a, A, V, M> Menu<'a, A, V, M>
+where
+    A: Copy,
+    V: Values<A>,
+{
+    /// Creates a menu of the entries of the submenu `root`, showing the settings in `values`.
+    pub fn new(state: &'a mut State, root: &'a Entry<'a, A>, values: &'a V) -> Self {
+        // The path is only kept as far as it leads to submenus, in case the tree has changed
+        let mut entries = root.entries();
+        let mut depth = 0;
+        for &index in state.path.iter() {
+            match entries.get(index) {
+                Some(&Entry::Submenu { entries: inner, .. }) => entries = inner,
+                _ => break,
+            }
+            depth += 1;
+        }
+        if depth < state.path.len() {
+            state.path.truncate(depth);
+            state.highlighted = 0;
+            state.editing = None;
+        }
+        let rows = state.row_count(entries.len());
+        state.highlighted = state.highlighted.min(rows.saturating_sub(1));
+
+        let on_choice = None;
+        Self {
+            state,
+            root,
+            entries,
+            values,
+            on_choice,
+        }
+    }
+
+    /// Sets the function that creates the message emitted when an entry is chosen.
+    pub fn on_choice(mut self, on_choice: fn(Choice<A>) -> M) -> Self {
+        self.on_choice = Some(on_choice);
+        self
+    }
+
+    fn row(&self, index: usize) -> Option<Row<'a, A>> {
+        match index.checked_sub(self.state.row_count(0)) {
+            None => Some(Row::Back),
+            Some(index) => self.entries.get(index).map(Row::Entry),
+        }
+    }
+
+    fn emit<T>(&self, context: &mut widget::EventContext<T, M>, choice: Choice<A>) {
+        if let Some(on_choice) = self.on_choice {
+            context.emit(on_choice(choice));
+        }
+    }
+
+    /// Moves the highlight by one row, and returns whether it could be moved.
+    fn move_highlight(&mut self, down: bool) -> bool {
+        let rows = self.state.row_count(self.entries.len());
+        let highlighted = self.state.highlighted;
+        let next = if down {
+            Some(highlighted + 1).filter(|&index| index < rows)
+        } else {
+            highlighted.checked_sub(1)
+        };
+        match next {
+            Some(index) => {
+                self.state.highlighted = index;
+                self.state.scroll_to_highlight(rows);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Activates the row at `index`.
+    fn activate<T>(&mut self, context: &mut widget::EventContext<T, M>, index: usize) {
+        self.state.highlighted = index;
+        match self.row(index) {
+            Some(Row::Back) => {
+                self.back();
+            }
+            Some(Row::Entry(&Entry::Submenu { entries, .. })) => {
+                if self
+                    .state
+                    .path
+                    .push(index - self.state.row_count(0))
+                    .is_ok()
+                {
+                    self.entries = entries;
+                    self.state.highlighted = 1.min(entries.len());
+                    self.state.first = 0;
+                }
+            }
+            Some(Row::Entry(&Entry::Action { action, .. })) => {
+                self.emit(context, Choice::Action(action));
+            }
+            Some(Row::Entry(&Entry::Toggle { toggle, .. })) => {
+                let on = !self.values.is_on(toggle);
+                self.emit(context, Choice::Toggle(toggle, on));
+            }
+            Some(Row::Entry(&Entry::Value { value, .. })) => {
+                self.state.editing = Some(self.values.value(value));
+            }
+            None => {}
+        }
+    }
+
+    /// Goes back to the parent menu, highlighting the submenu that was left, and returns whether
+    /// there was a parent.
+    fn back(&mut self) -> bool {
+        let index = match self.state.path.pop() {
+            Some(index) => index,
+            None => return false,
+        };
+        let mut entries = self.root.entries();
+        for &index in self.state.path.iter() {
+            entries = entries[index].entries();
+        }
+        self.entries = entries;
+        self.state.highlighted = index + self.state.row_count(0);
+        let rows = self.state.row_count(entries.len());
+        self.state.scroll_to_highlight(rows);
+        true
+    }
+
+    /// Changes the value being edited by `steps` steps.
+    fn edit<T>(&mut self, context: &mut widget::EventContext<T, M>, steps: i32) {
+        let highlighted = self.state.highlighted;
+        if let (Some(current), Some(Row::Entry(entry))) =
+            (self.state.editing, self.row(highlighted))
+        {
+            if let Entry::Value {
+                value,
+                min,
+                max,
+                step,
+                ..
+            } = *entry
+            {
+                let edited = current.saturating_add(steps * step).max(min).min(max);
+                if edited != current {
+                    self.state.editing = Some(edited);
+                    self.emit(context, Choice::Value(value, edited));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, A, V, T, M> widget::Node<'a, T, M> for Menu<'a, A, V, M>
+where
+    A: Copy,
+    V: Values<A>,
+    T: theme::Theme<'a>,
+{
+    fn visit<W>(&self, visitor: W) -> W::Output
+    where
+        W: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<W>(&mut self, visitor: W) -> W::Output
+    where
+        W: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, A, V, T, M> widget::Widget<'a, T, M> for Menu<'a, A, V, M>
+where
+    A: Copy,
+    V: Values<A>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<
+            util::Glyphs<'a, T, TitleCapacity>,
+            util::Pixels<'a, T, Rows<'a, T, A, V>>,
+        >,
+        core::iter::Chain<util::RectIter<T::Color>, util::OptionalIter<util::RectIter<T::Color>>>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let theme = context.theme;
+        let state = &*self.state;
+        let glyph_size = T::Font::render_str("0").size();
+        let row_height = row_height(theme, glyph_size.height);
+        let visible = visible_rows(context.size, row_height);
+        let rows = state.row_count(self.entries.len());
+        let first = state.first.min(rows.saturating_sub(visible));
+
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let scrollbar_width = if rows > visible {
+            theme.scrollbar_width() as i32
+        } else {
+            0
+        };
+        let grid = Grid {
+            x1,
+            x2: x2 - scrollbar_width,
+            y1,
+            row_height,
+            padding: theme.spacing() as i32,
+            advance: glyph_size.width.max(1) as i32,
+        };
+
+        // The breadcrumb keeps as many of the innermost submenus as fit
+        let fits = ((x2 - x1 + 1 - 2 * grid.padding) / grid.advance).max(0) as usize;
+        let mut labels = heapless::Vec::<&str, heapless::consts::U9>::new();
+        let _ = labels.push(self.root.label());
+        let mut entries = self.root.entries();
+        for &index in state.path.iter() {
+            let entry = &entries[index];
+            let _ = labels.push(entry.label());
+            entries = entry.entries();
+        }
+        let mut start = labels.len() - 1;
+        let mut width = labels[start].len();
+        while start > 0 {
+            let prefix = if start > 1 {
+                ELLIPSIS.len() + SEPARATOR.len()
+            } else {
+                0
+            };
+            let extended = width + SEPARATOR.len() + labels[start - 1].len();
+            if extended + prefix > fits {
+                break;
+            }
+            width = extended;
+            start -= 1;
+        }
+        let mut title = heapless::String::new();
+        if start > 0 {
+            util::push_truncated(&mut title, ELLIPSIS);
+            util::push_truncated(&mut title, SEPARATOR);
+        }
+        for (i, label) in labels[start..].iter().enumerate() {
+            if i > 0 {
+                util::push_truncated(&mut title, SEPARATOR);
+            }
+            util::push_truncated(&mut title, label);
+        }
+        let title_position =
+            embedded_graphics::geometry::Point::new(x1 + grid.padding, y1 + grid.padding);
+        let title = util::Glyphs::new(title, title_position, grid.advance, theme.text_color())
+            .range(0..fits);
+
+        let highlight_color = if state.editing.is_some() && context.focused {
+            theme.focus_color()
+        } else {
+            theme.fill_color()
+        };
+        let rows = Rows {
+            entries: self.entries,
+            values: self.values,
+            back: !state.path.is_empty(),
+            rows: first..rows.min(first + visible),
+            highlighted: state.highlighted,
+            editing: state.editing,
+            grid,
+            text_color: theme.text_color(),
+            highlight_color,
+            highlight_text_color: theme.background_color(),
+            row: first,
+            step: 0,
+        };
+
+        let separator_y = y1 + row_height;
+        let separator = egrectangle!(
+            (x1, separator_y),
+            (x2, separator_y),
+            stroke = Some(theme.border_color())
+        );
+
+        let scrollbar = if rows > visible {
+            let top = separator_y + 1;
+            let track = y2 - top + 1;
+            let thumb = (track * visible as i32 / rows as i32).max(2);
+            let y = top + (track - thumb) * first as i32 / (rows - visible) as i32;
+            Some(
+                egrectangle!(
+                    (x2 - scrollbar_width + 1, y),
+                    (x2, y + thumb - 1),
+                    fill = Some(theme.fill_color())
+                )
+                .into_iter(),
+            )
+        } else {
+            None
+        };
+
+        title
+            .chain(util::Pixels::new(rows))
+            .chain(separator.into_iter().chain(scrollbar.into_iter().flatten()))
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        // At least the title and a single row
+        let glyph_height = T::Font::render_str("0").size().height;
+        let height = 2 * row_height(theme, glyph_height) + 1;
+
+        layout::Style {
+            flex_grow: 1.0,
+            flex_shrink: 1.0,
+            align_self: stretch::style::AlignSelf::Stretch,
+            min_size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height: stretch::style::Dimension::Points(height as f32),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let glyph_height = T::Font::render_str("0").size().height;
+        let row_height = row_height(context.theme, glyph_height).max(1);
+        let rows = self.state.row_count(self.entries.len());
+        self.state.visible = visible_rows(context.size, row_height);
+        self.state.first = self
+            .state
+            .first
+            .min(rows.saturating_sub(self.state.visible));
+
+        match (context.phase, event) {
+            (event::Phase::Capture, _) => false,
+            (_, event::Event::Pointer(pointer)) => {
+                // The title is row `None`, and tapping it goes back like the first row
+                let y = pointer.position.y - context.position.y - row_height - 1;
+                let row = if y < 0 {
+                    None
+                } else {
+                    Some(self.state.first + (y / row_height) as usize)
+                };
+                // While a value is edited, tapping its row steps it, and pressing anywhere else
+                // leaves the editing mode
+                let on_edited_row =
+                    self.state.editing.is_some() && row == Some(self.state.highlighted);
+                match pointer.action {
+                    event::PointerAction::Down => {
+                        if !on_edited_row {
+                            self.state.editing = None;
+                        }
+                        self.state.pressed = Some(row.unwrap_or(usize::max_value()));
+                        true
+                    }
+                    event::PointerAction::Move => self.state.pressed.is_some(),
+                    event::PointerAction::Up => {
+                        let pressed = self.state.pressed.take();
+                        let released = row.unwrap_or(usize::max_value());
+                        if pressed == Some(released) && context.contains(pointer.position) {
+                            match row {
+                                Some(_) if on_edited_row => {
+                                    // The left half of the row steps down, the right half up
+                                    let middle = context.position.x + context.size.width as i32 / 2;
+                                    let steps = if pointer.position.x < middle { -1 } else { 1 };
+                                    self.edit(context, steps);
+                                }
+                                Some(index) if index < rows => self.activate(context, index),
+                                Some(_) => {}
+                                None => {
+                                    self.back();
+                                }
+                            }
+                        }
+                        pressed.is_some()
+                    }
+                }
+            }
+            (event::Phase::Target, event::Event::Navigation(navigation))
+                if self.state.editing.is_some() =>
+            {
+                match *navigation {
+                    event::Navigation::Next | event::Navigation::Up | event::Navigation::Right => {
+                        self.edit(context, 1)
+                    }
+                    event::Navigation::Previous
+                    | event::Navigation::Down
+                    | event::Navigation::Left => self.edit(context, -1),
+                    event::Navigation::Activate | event::Navigation::Back => {
+                        self.state.editing = None
+                    }
+                }
+                true
+            }
+            (event::Phase::Target, event::Event::Navigation(navigation)) => match *navigation {
+                event::Navigation::Activate => {
+                    let index = self.state.highlighted;
+                    self.activate(context, index);
+                    true
+                }
+                event::Navigation::Back | event::Navigation::Left => self.back(),
+                event::Navigation::Right => match self.row(self.state.highlighted) {
+                    Some(Row::Entry(Entry::Submenu { .. })) => {
+                        let index = self.state.highlighted;
+                        self.activate(context, index);
+                        true
+                    }
+                    _ => false,
+                },
+                // Past the first and last row, the focus leaves the menu
+                event::Navigation::Next | event::Navigation::Down => self.move_highlight(true),
+                event::Navigation::Previous | event::Navigation::Up => self.move_highlight(false),
+            },
+            _ => false,
+        }
+    }
+}
+
+impl<'a, T, A, V> util::Parts<'a, T> for Rows<'a, T, A, V>
+where
+    T: theme::Theme<'a>,
+    A: Copy,
+    V: Values<A>,
+{
+    type Capacity = DetailCapacity;
+
+    /// Moves on to the next part of the menu: the highlight, label and detail of every visible row.
+    fn advance(&mut self) -> Option<util::Part<'a, T, DetailCapacity>> {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        loop {
+            let index = self.row;
+            if index >= self.rows.end {
+                return None;
+            }
+            let step = self.step;
+            self.step += 1;
+            if step > 2 {
+                self.row += 1;
+                self.step = 0;
+                continue;
+            }
+
+            let grid = self.grid;
+            let y = grid.y1 + 1 + (index - self.rows.start + 1) as i32 * grid.row_height;
+            let highlighted = index == self.highlighted;
+            let color = if highlighted {
+                self.highlight_text_color
+            } else {
+                self.text_color
+            };
+            let entry = match index.checked_sub(if self.back { 1 } else { 0 }) {
+                Some(index) => self.entries.get(index),
+                None => None,
+            };
+
+            match step {
+                0 if highlighted => {
+                    return Some(util::Part::Rect(
+                        egrectangle!(
+                            (grid.x1, y),
+                            (grid.x2, y + grid.row_height - 1),
+                            fill = Some(self.highlight_color)
+                        )
+                        .into_iter(),
+                    ));
+                }
+                1 => {
+                    let label = entry.map_or(BACK, Entry::label);
+                    let position = embedded_graphics::geometry::Point::new(
+                        grid.x1 + grid.padding,
+                        y + grid.padding,
+                    );
+                    return Some(util::Part::Text(
+                        T::Font::render_str(label)
+                            .translate(position)
+                            .stroke(Some(color))
+                            .into_iter(),
+                    ));
+                }
+                2 => {
+                    let mut detail = heapless::String::<DetailCapacity>::new();
+                    match entry {
+                        Some(Entry::Submenu { .. }) => util::push_truncated(&mut detail, ">"),
+                        Some(&Entry::Toggle { toggle, .. }) => {
+                            let on = self.values.is_on(toggle);
+                            util::push_truncated(&mut detail, if on { "On" } else { "Off" });
+                        }
+                        Some(&Entry::Value { value, .. }) => match self.editing {
+                            Some(editing) if highlighted => {
+                                let _ = write!(detail, "<{}>", editing);
+                            }
+                            _ => {
+                                let _ = write!(detail, "{}", self.values.value(value));
+                            }
+                        },
+                        Some(Entry::Action { .. }) | None => continue,
+                    }
+                    let x = grid.x2 - grid.padding - detail.len() as i32 * grid.advance + 1;
+                    let position = embedded_graphics::geometry::Point::new(x, y + grid.padding);
+                    return Some(util::Part::Glyphs(util::Glyphs::new(
+                        detail,
+                        position,
+                        grid.advance,
+                        color,
+                    )));
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+/// The height of a row of a menu, including the title.
+fn row_height<'a, T>(theme: &T, glyph_height: u32) -> i32
+where
+    T: theme::Theme<'a>,
+{
+    glyph_height as i32 + 2 * theme.spacing() as i32
+}
+
+/// The number of rows that fit below the title of a menu with size `size`.
+fn visible_rows(size: embedded_graphics::geometry::Size, row_height: i32) -> usize {
+    // The title and the line under it
+    let height = size.height as i32 - 1 - row_height;
+    (height / row_height.max(1)).max(0) as usize
+}
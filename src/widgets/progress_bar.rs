@@ -22,26 +22,26 @@ impl<'a> ProgressBar<'a> {
     }
 }
 
-impl<'a, T> widget::Node<'a, T> for ProgressBar<'a>
+impl<'a, T, M> widget::Node<'a, T, M> for ProgressBar<'a>
 where
     T: theme::Theme<'a>,
 {
     fn visit<V>(&self, visitor: V) -> V::Output
     where
-        V: widget::Visitor<'a, T>,
+        V: widget::Visitor<'a, T, M>,
     {
         visitor.accept_leaf(self)
     }
 
     fn visit_mut<V>(&mut self, visitor: V) -> V::Output
     where
-        V: widget::MutVisitor<'a, T>,
+        V: widget::MutVisitor<'a, T, M>,
     {
         visitor.accept_leaf_mut(self)
     }
 }
 
-impl<'a, T> widget::Widget<'a, T> for ProgressBar<'a>
+impl<'a, T, M> widget::Widget<'a, T, M> for ProgressBar<'a>
 where
     T: theme::Theme<'a>,
 {
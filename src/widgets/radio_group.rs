@@ -0,0 +1,391 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use crate::widgets::container;
+use core::borrow::Borrow;
+use core::borrow::BorrowMut;
+use core::marker;
+
+pub type Row<'a, M> = RadioGroup<'a, container::RowBase, M>;
+
+pub type Column<'a, M> = RadioGroup<'a, container::ColumnBase, M>;
+
+/// The maximum number of options in a radio group; any further options are ignored.
+pub type Capacity = heapless::consts::U8;
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    options: heapless::Vec<layout::State, Capacity>,
+    selection: Selection,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Selection {
+    selected: usize,
+    pressed: Option<usize>,
+    editing: bool,
+}
+
+/// A set of labelled options, of which exactly one is selected.
+///
+/// Each option is laid out as a child of the group, like the children of a `container::Container`.
+/// The group itself is focusable, and while it has the focus, the arrow keys along its direction
+/// select the previous or next option.  In editing mode, which `Activate` toggles, `Next` and
+/// `Previous` also move the selection through the options rather than the focus through the
+/// widgets; see `event::Navigation`.
+#[derive(Debug)]
+pub struct RadioGroup<'a, B, M> {
+    state: &'a mut State,
+    options: &'a [&'a str],
+    on_select: Option<fn(usize) -> M>,
+    phantom: marker::PhantomData<B>,
+}
+
+/// A single option, which borrows its layout state and the selection from the group's `State`
+/// while it is visited, or holds copies of them if the visitor doesn't need to modify them.
+struct Choice<'a, L, S, M> {
+    layout: L,
+    selection: S,
+    label: &'a str,
+    index: usize,
+    on_select: Option<fn(usize) -> M>,
+}
+
+impl State {
+    /// The index of the selected option.
+    pub fn selected(&self) -> usize {
+        self.selection.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selection.selected = index;
+    }
+}
+
+impl<'a, B, M> RadioGroup<'a, B, M>
+where
+    B: container::Base,
+{
+    /// Creates a group of the `options`, of which only the first `Capacity` (8) are shown; any
+    /// further options are ignored.
+    pub fn new(state: &'a mut State, options: &'a [&'a str]) -> Self {
+        let on_select = None;
+        let phantom = marker::PhantomData;
+        Self {
+            state,
+            options,
+            on_select,
+            phantom,
+        }
+    }
+
+    /// Sets the function that creates the message emitted with the index of an option when it is
+    /// selected.
+    pub fn on_select(mut self, on_select: fn(usize) -> M) -> Self {
+        self.on_select = Some(on_select);
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.options.len().min(self.state.options.capacity())
+    }
+
+    fn select<T>(&mut self, context: &mut widget::EventContext<T, M>, index: usize) {
+        self.state.select(index);
+        if let Some(on_select) = self.on_select {
+            context.emit(on_select(index));
+        }
+    }
+}
+
+impl<'a, B, T, M> widget::Node<'a, T, M> for RadioGroup<'a, B, M>
+where
+    B: container::Base,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let len = self.len();
+        let mut visitor = visitor.accept_node(self, len);
+        for (index, &label) in self.options[..len].iter().enumerate() {
+            let layout = self.state.options.get(index).cloned().unwrap_or_default();
+            visitor.accept_child(&Choice {
+                layout,
+                selection: self.state.selection,
+                label,
+                index,
+                on_select: self.on_select,
+            });
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let len = self.len();
+        let _ = self.state.options.resize_default(len);
+        let options = self.options;
+        let on_select = self.on_select;
+
+        let mut visitor = visitor.accept_node_mut(self, len);
+        let State {
+            options: layouts,
+            selection,
+            ..
+        } = &mut *self.state;
+        for (index, (layout, &label)) in layouts.iter_mut().zip(options).enumerate() {
+            visitor.accept_child_mut(&mut Choice {
+                layout,
+                selection: &mut *selection,
+                label,
+                index,
+                on_select,
+            });
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, B, T, M> widget::Widget<'a, T, M> for RadioGroup<'a, B, M>
+where
+    B: container::Base,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        layout::Style {
+            flex_shrink: 1.0,
+            flex_basis: stretch::style::Dimension::Auto,
+            flex_wrap: stretch::style::FlexWrap::Wrap,
+            align_items: stretch::style::AlignItems::FlexStart,
+            flex_direction: B::FLEX_DIRECTION,
+            size: B::SIZE,
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.len() > 0
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let navigation = match (context.phase, event) {
+            (event::Phase::Target, event::Event::Navigation(navigation)) => *navigation,
+            _ => return false,
+        };
+        let (previous, next) = match B::FLEX_DIRECTION {
+            stretch::style::FlexDirection::Row | stretch::style::FlexDirection::RowReverse => {
+                (event::Navigation::Left, event::Navigation::Right)
+            }
+            _ => (event::Navigation::Up, event::Navigation::Down),
+        };
+
+        let editing = self.state.selection.editing;
+        if navigation == event::Navigation::Activate {
+            self.state.selection.editing = !editing;
+            return true;
+        }
+
+        // Past the first and last option, the arrows move the focus out of the group, but not while
+        // the selection is being edited
+        let selected = self.state.selected();
+        let (back, forward) = if editing {
+            (
+                navigation == previous || navigation == event::Navigation::Previous,
+                navigation == next || navigation == event::Navigation::Next,
+            )
+        } else {
+            (navigation == previous, navigation == next)
+        };
+        if back && selected > 0 {
+            self.select(context, selected - 1);
+            true
+        } else if forward && selected + 1 < self.len() {
+            self.select(context, selected + 1);
+            true
+        } else {
+            editing && (back || forward)
+        }
+    }
+}
+
+impl<'a, L, S, T, M> widget::Node<'a, T, M> for Choice<'a, L, S, M>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    S: Borrow<Selection> + BorrowMut<Selection>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, L, S, T, M> widget::Widget<'a, T, M> for Choice<'a, L, S, M>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    S: Borrow<Selection> + BorrowMut<Selection>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<
+            util::CircleIter<T::Color>,
+            util::OptionalIter<util::CircleIter<T::Color>>,
+        >,
+        util::FontIter<'a, T>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let theme = context.theme;
+        let text = T::Font::render_str(self.label);
+        let text_size = text.size();
+
+        // The indicator is a circle as high as the label
+        let radius = text_size.height as i32 / 2;
+        let cx = context.position.x + radius;
+        let cy = context.position.y + context.size.height as i32 / 2;
+
+        let fill = if context.hovered {
+            Some(theme.hover_color())
+        } else {
+            None
+        };
+        let circle_iter = embedded_graphics::egcircle!(
+            (cx, cy),
+            radius as u32,
+            fill = fill,
+            stroke = Some(theme.border_color())
+        )
+        .into_iter();
+
+        // The dot contrasts with the circle, which is filled while hovered
+        let selection = self.selection.borrow();
+        let dot_color = if selection.editing {
+            theme.focus_color()
+        } else if context.hovered {
+            theme.background_color()
+        } else {
+            theme.fill_color()
+        };
+        let dot_iter = if selection.selected == self.index {
+            Some(
+                embedded_graphics::egcircle!(
+                    (cx, cy),
+                    (radius - 2).max(1) as u32,
+                    fill = Some(dot_color)
+                )
+                .into_iter(),
+            )
+        } else {
+            None
+        };
+
+        let xt = context.position.x + text_size.height as i32 + theme.spacing() as i32;
+        let yt = context.position.y + (context.size.height as i32 - text_size.height as i32) / 2;
+        let text_iter = text
+            .translate(embedded_graphics::geometry::Point::new(xt, yt))
+            .stroke(Some(theme.text_color()))
+            .into_iter();
+
+        circle_iter
+            .chain(dot_iter.into_iter().flatten())
+            .chain(text_iter)
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let size = T::Font::render_str(self.label).size();
+        let width = size.height as f32 + theme.spacing() + size.width as f32;
+        layout::Style {
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(width),
+                height: stretch::style::Dimension::Points(size.height as f32),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        self.layout.borrow()
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        self.layout.borrow_mut()
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let pointer = match event {
+            event::Event::Pointer(pointer) => pointer,
+            _ => return false,
+        };
+
+        let index = self.index;
+        let selection = self.selection.borrow_mut();
+        match pointer.action {
+            event::PointerAction::Down => {
+                selection.pressed = Some(index);
+                true
+            }
+            event::PointerAction::Up => {
+                if selection.pressed == Some(index) && context.contains(pointer.position) {
+                    selection.selected = index;
+                    selection.editing = false;
+                    if let Some(on_select) = self.on_select {
+                        context.emit(on_select(index));
+                    }
+                }
+                selection.pressed = None;
+                true
+            }
+            event::PointerAction::Move => false,
+        }
+    }
+}
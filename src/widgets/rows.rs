@@ -0,0 +1,187 @@
+//! Scrolling through equally high rows by whole rows and highlighting one of them, as list views
+//! and tables do.
+use crate::event;
+use crate::util;
+use crate::widget;
+use embedded_graphics::egrectangle;
+
+/// Which of `len` rows are visible, of which `visible` fit at once, and which one is highlighted.
+///
+/// The rows scroll by whole rows when the pointer is dragged over them, and tapping a row chooses
+/// it.  The up and down arrows move the highlight, as do `Next` and `Previous` while `editing`;
+/// leaving that mode chooses the highlighted row.
+#[derive(Debug, Default)]
+pub(crate) struct Rows {
+    pub(crate) len: usize,
+    pub(crate) visible: usize,
+    pub(crate) first: usize,
+    pub(crate) highlighted: Option<usize>,
+    pub(crate) editing: bool,
+    drag: Option<Drag>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    y: i32,
+    first: usize,
+    moved: bool,
+}
+
+/// What an event did to the rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    /// The event was not handled.
+    Ignored,
+    /// The event was handled, e.g. by scrolling the rows or moving the highlight.
+    Handled,
+    /// The row at this index was chosen, by tapping it or by leaving the editing mode.
+    Chosen(usize),
+}
+
+impl Rows {
+    /// Updates the number of rows, keeping the first visible row and the highlight within them.
+    pub(crate) fn set_len(&mut self, len: usize) {
+        self.len = len;
+        self.clamp();
+        self.highlighted = self.highlighted.filter(|&index| index < len);
+    }
+
+    /// Scrolls as little as possible to make the row at `index` visible.
+    pub(crate) fn scroll_to(&mut self, index: usize) {
+        if index < self.first {
+            self.first = index;
+        } else if index >= self.first + self.visible {
+            self.first = index + 1 - self.visible.max(1);
+        }
+        self.clamp();
+    }
+
+    /// Keeps the visible rows filled.
+    pub(crate) fn clamp(&mut self) {
+        self.first = self.first.min(self.len.saturating_sub(self.visible));
+    }
+
+    /// The indices of the visible rows.
+    pub(crate) fn range(&self) -> core::ops::Range<usize> {
+        self.first..self.len.min(self.first + self.visible)
+    }
+
+    /// Moves the highlight by one row, and returns whether it could be moved.
+    pub(crate) fn highlight(&mut self, down: bool) -> bool {
+        let current = self.highlighted.unwrap_or(self.first);
+        let next = if self.highlighted.is_none() {
+            Some(current)
+        } else if down {
+            Some(current + 1).filter(|&index| index < self.len)
+        } else {
+            current.checked_sub(1)
+        };
+
+        match next {
+            Some(index) if self.len > 0 => {
+                self.highlighted = Some(index);
+                self.scroll_to(index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Scrolls the rows while the pointer is dragged over them, where the first visible row starts
+    /// at `top`.
+    pub(crate) fn drag<T, M>(
+        &mut self,
+        context: &widget::EventContext<T, M>,
+        pointer: &event::Pointer,
+        top: i32,
+        row_height: i32,
+    ) -> Outcome {
+        let row_height = row_height.max(1);
+        let y = pointer.position.y;
+        match pointer.action {
+            event::PointerAction::Down => {
+                let first = self.first;
+                let moved = false;
+                self.drag = Some(Drag { y, first, moved });
+                Outcome::Handled
+            }
+            event::PointerAction::Move => match self.drag.as_mut() {
+                Some(drag) => {
+                    let rows = (drag.y - y) / row_height;
+                    drag.moved |= rows != 0;
+                    self.first = (drag.first as i32 + rows).max(0) as usize;
+                    self.clamp();
+                    Outcome::Handled
+                }
+                None => Outcome::Ignored,
+            },
+            event::PointerAction::Up => {
+                let tapped = self.drag.take().map_or(false, |drag| !drag.moved);
+                if tapped && context.contains(pointer.position) && y >= top {
+                    let index = self.first + ((y - top) / row_height) as usize;
+                    if self.range().contains(&index) {
+                        return Outcome::Chosen(index);
+                    }
+                }
+                Outcome::Handled
+            }
+        }
+    }
+
+    /// Moves the highlight in response to a navigation event.
+    pub(crate) fn navigate(&mut self, navigation: event::Navigation) -> Outcome {
+        let handled = match navigation {
+            event::Navigation::Activate if self.editing => {
+                self.editing = false;
+                return self.highlighted.map_or(Outcome::Handled, Outcome::Chosen);
+            }
+            event::Navigation::Activate => {
+                self.editing = true;
+                if self.highlighted.is_none() {
+                    self.highlight(true);
+                }
+                true
+            }
+            event::Navigation::Next if self.editing => {
+                self.highlight(true);
+                true
+            }
+            event::Navigation::Previous if self.editing => {
+                self.highlight(false);
+                true
+            }
+            // The arrows are left to move the focus above the first row and below the last
+            event::Navigation::Down => self.highlight(true),
+            event::Navigation::Up => self.highlight(false),
+            _ => false,
+        };
+        if handled {
+            Outcome::Handled
+        } else {
+            Outcome::Ignored
+        }
+    }
+
+    /// The thumb of a scrollbar `width` pixels wide inside the right edge `x2` of a widget, which
+    /// moves between `top` and its bottom edge `y2`, if not all rows are visible.
+    pub(crate) fn scrollbar<C>(
+        &self,
+        x2: i32,
+        top: i32,
+        y2: i32,
+        width: i32,
+        color: C,
+    ) -> Option<util::RectIter<C>>
+    where
+        C: embedded_graphics::pixelcolor::PixelColor,
+    {
+        if self.len <= self.visible {
+            return None;
+        }
+
+        let track = y2 - top;
+        let thumb = (track * self.visible as i32 / self.len as i32).max(2);
+        let y = top + (track - thumb) * self.first as i32 / (self.len - self.visible) as i32;
+        Some(egrectangle!((x2 - width, y), (x2 - 1, y + thumb - 1), fill = Some(color)).into_iter())
+    }
+}
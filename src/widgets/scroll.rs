@@ -0,0 +1,590 @@
+use crate::event;
+use crate::focus;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::time;
+use embedded_graphics::egrectangle;
+
+/// How quickly a flung scroll container comes to rest, in pixels per second squared.
+const DECELERATION: f32 = 1500.0;
+
+/// The directions in which a scroll container scrolls its content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// The content is as wide as the container, and as high as it needs to be.
+    Vertical,
+    /// The content is as high as the container, and as wide as it needs to be.
+    Horizontal,
+    /// The content is as wide and as high as it needs to be.
+    Both,
+}
+
+/// The state of a scroll container.
+#[derive(Debug, Default)]
+pub struct State {
+    view: View,
+    viewport: layout::State,
+    content: layout::State,
+}
+
+#[derive(Debug, Default)]
+struct View {
+    layout: layout::State,
+    offset: layout::Point,
+    drag: Option<Drag>,
+    fling: Option<Fling>,
+    /// The widget that had the focus in the last frame.
+    focus: Option<widget::Id>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    position: embedded_graphics::geometry::Point,
+    offset: layout::Point,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Fling {
+    /// The speed at which the offset changes, in pixels per second.
+    velocity: layout::Point,
+    time: time::Duration,
+}
+
+/// A container that shows a part of a child that is larger than itself.
+///
+/// The child is clipped to the bounds of the container and moved by the scroll offset, and
+/// scrollbars drawn along the right and bottom edges show which part of it is visible.  Dragging
+/// the pointer over the container scrolls the child along with it, and swiping flings it so that
+/// it keeps scrolling for a while after the pointer is lifted.  Drags that start on a widget that
+/// handles pointer presses itself, such as a button, are left to that widget, but swipes are not.
+/// When the focus moves to a widget in the child, the container scrolls as little as possible to
+/// show it.
+///
+/// The container grows to fill the space available in its parent rather than to fit its child.
+#[derive(Debug)]
+pub struct Scroll<'a, N> {
+    view: &'a mut View,
+    direction: Direction,
+    viewport: Viewport<'a, N>,
+}
+
+/// The area that the content is clipped to, inside of the scrollbars.
+#[derive(Debug)]
+struct Viewport<'a, N> {
+    layout: &'a mut layout::State,
+    offset: layout::Point,
+    content: Content<'a, N>,
+}
+
+/// The child laid out at its own size, independently of the size of the viewport.
+#[derive(Debug)]
+struct Content<'a, N> {
+    layout: &'a mut layout::State,
+    direction: Direction,
+    child: N,
+}
+
+impl State {
+    /// How far the content is scrolled to the left and up.
+    pub fn offset(&self) -> layout::Point {
+        self.view.offset
+    }
+
+    /// Scrolls the content to `offset`, which is limited to the size of the content when the
+    /// container is next updated.
+    pub fn scroll_to(&mut self, offset: layout::Point) {
+        self.view.offset = offset;
+        self.view.fling = None;
+    }
+}
+
+impl Direction {
+    fn horizontal(self) -> bool {
+        self != Direction::Vertical
+    }
+
+    fn vertical(self) -> bool {
+        self != Direction::Horizontal
+    }
+}
+
+impl<'a, N> Scroll<'a, N> {
+    /// Creates a container that scrolls `child` vertically.
+    pub fn new(state: &'a mut State, child: N) -> Self {
+        let State {
+            view,
+            viewport,
+            content,
+        } = state;
+        let direction = Direction::Vertical;
+        let offset = view.offset;
+        let content = Content {
+            layout: content,
+            direction,
+            child,
+        };
+        let viewport = Viewport {
+            layout: viewport,
+            offset,
+            content,
+        };
+        Self {
+            view,
+            direction,
+            viewport,
+        }
+    }
+
+    /// Sets the directions in which the content scrolls.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self.viewport.content.direction = direction;
+        self
+    }
+
+    /// The largest offset that still shows content everywhere in the viewport, given a function
+    /// that looks up the size of a widget from its layout state.
+    fn limit<F>(&self, size_of: F) -> layout::Point
+    where
+        F: Fn(&layout::State) -> Option<embedded_graphics::geometry::Size>,
+    {
+        let viewport = size_of(&*self.viewport.layout).unwrap_or_default();
+        let content = size_of(&*self.viewport.content.layout).unwrap_or_default();
+        layout::Point {
+            x: content.width.saturating_sub(viewport.width) as f32,
+            y: content.height.saturating_sub(viewport.height) as f32,
+        }
+    }
+
+    /// Scrolls to `offset` as far as `limit` and the direction allow, and returns whether the
+    /// offset was limited.
+    fn scroll_to(&mut self, offset: layout::Point, limit: layout::Point) -> bool {
+        let clamp = |offset: f32, limit: f32, enabled: bool| {
+            if enabled {
+                offset.max(0.0).min(limit)
+            } else {
+                0.0
+            }
+        };
+        let clamped = layout::Point {
+            x: clamp(offset.x, limit.x, self.direction.horizontal()),
+            y: clamp(offset.y, limit.y, self.direction.vertical()),
+        };
+        self.view.offset = clamped;
+        self.viewport.offset = clamped;
+        clamped != offset
+    }
+}
+
+impl<'a, N, T, M> widget::Node<'a, T, M> for Scroll<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, 1);
+        visitor.accept_child(&self.viewport);
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let mut visitor = visitor.accept_node_mut(self, 1);
+        visitor.accept_child_mut(&mut self.viewport);
+        visitor.end()
+    }
+}
+
+impl<'a, N, T, M> widget::Widget<'a, T, M> for Scroll<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        util::OptionalIter<util::RectIter<T::Color>>,
+        util::OptionalIter<util::RectIter<T::Color>>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let theme = context.theme;
+        let width = theme.scrollbar_width() as i32;
+        let viewport = context.size_of(&*self.viewport.layout).unwrap_or_default();
+        let limit = self.limit(|state| context.size_of(state));
+        let offset = self.view.offset;
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+
+        // The length of a thumb and its distance from the start of the track
+        let thumb = |track: i32, offset: f32, limit: f32| {
+            let length = (track as f32 * track as f32 / (track as f32 + limit)) as i32;
+            let length = length.max(width).min(track);
+            let start = ((track - length) as f32 * offset / limit) as i32;
+            (start, length)
+        };
+
+        let vertical = if self.direction.vertical() && limit.y > 0.0 {
+            let (start, length) = thumb(viewport.height as i32, offset.y, limit.y);
+            Some(
+                egrectangle!(
+                    (x2 - width + 1, y1 + start),
+                    (x2, y1 + start + length - 1),
+                    fill = Some(theme.fill_color())
+                )
+                .into_iter(),
+            )
+        } else {
+            None
+        };
+
+        let horizontal = if self.direction.horizontal() && limit.x > 0.0 {
+            let (start, length) = thumb(viewport.width as i32, offset.x, limit.x);
+            Some(
+                egrectangle!(
+                    (x1 + start, y2 - width + 1),
+                    (x1 + start + length - 1, y2),
+                    fill = Some(theme.fill_color())
+                )
+                .into_iter(),
+            )
+        } else {
+            None
+        };
+
+        vertical
+            .into_iter()
+            .flatten()
+            .chain(horizontal.into_iter().flatten())
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        let bar = |enabled: bool| {
+            if enabled {
+                stretch::style::Dimension::Points(theme.scrollbar_width())
+            } else {
+                stretch::style::Dimension::Points(0.0)
+            }
+        };
+
+        layout::Style {
+            flex_grow: 1.0,
+            flex_shrink: 1.0,
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_self: stretch::style::AlignSelf::Stretch,
+            align_items: stretch::style::AlignItems::Stretch,
+            padding: stretch::geometry::Rect {
+                start: stretch::style::Dimension::Points(0.0),
+                end: bar(self.direction.vertical()),
+                top: stretch::style::Dimension::Points(0.0),
+                bottom: bar(self.direction.horizontal()),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.view.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.view.layout
+    }
+
+    fn animate(&mut self, context: &widget::AnimationContext) {
+        // The content may have changed size since the last frame
+        let limit = self.limit(|state| context.size_of(state));
+        let mut offset = self.view.offset;
+
+        // Only when the focus moves, so that the focused widget can still be scrolled out of view
+        if context.focus != self.view.focus {
+            self.view.focus = context.focus;
+            let mut focused = None;
+            focus::for_each_focusable::<T, M, _, _>(
+                context.layout,
+                &self.viewport.content,
+                |id, geometry| {
+                    if Some(id) == context.focus {
+                        focused = Some(geometry);
+                    }
+                },
+            );
+
+            // Show the whole of the focused widget, or at least its top left corner
+            let show = |offset: f32, start: f32, length: f32, viewport: f32| {
+                if start < offset {
+                    start
+                } else if start + length > offset + viewport {
+                    (start + length - viewport).min(start)
+                } else {
+                    offset
+                }
+            };
+            if let Some(geometry) = focused {
+                let viewport = context.size_of(&*self.viewport.layout).unwrap_or_default();
+                let layout::Geometry { position, size } = geometry;
+                offset = layout::Point {
+                    x: show(offset.x, position.x, size.width, viewport.width as f32),
+                    y: show(offset.y, position.y, size.height, viewport.height as f32),
+                };
+                self.view.fling = None;
+            }
+        }
+        self.scroll_to(offset, limit);
+
+        let fling = match self.view.fling.take() {
+            Some(fling) => fling,
+            None => return,
+        };
+        let elapsed = context.now.checked_sub(fling.time).unwrap_or_default();
+        let seconds = elapsed.as_millis() as f32 / 1000.0;
+
+        // The speed drops linearly, and the offset moves by the average speed over the frame
+        let decelerate = |speed: f32| {
+            if speed > 0.0 {
+                (speed - DECELERATION * seconds).max(0.0)
+            } else {
+                (speed + DECELERATION * seconds).min(0.0)
+            }
+        };
+        let velocity = layout::Point {
+            x: decelerate(fling.velocity.x),
+            y: decelerate(fling.velocity.y),
+        };
+        let offset = layout::Point {
+            x: offset.x + (fling.velocity.x + velocity.x) / 2.0 * seconds,
+            y: offset.y + (fling.velocity.y + velocity.y) / 2.0 * seconds,
+        };
+
+        let stopped = self.scroll_to(offset, limit);
+        if !stopped && velocity != layout::Point::default() {
+            let time = context.now;
+            self.view.fling = Some(Fling { velocity, time });
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        match (context.phase, event) {
+            // Touching the content stops a fling, whichever widget handles the touch
+            (_, event::Event::Pointer(pointer)) if pointer.action == event::PointerAction::Down => {
+                self.view.fling = None;
+                if context.phase == event::Phase::Capture {
+                    return false;
+                }
+                let position = pointer.position;
+                let offset = self.view.offset;
+                self.view.drag = Some(Drag { position, offset });
+                true
+            }
+            (event::Phase::Capture, _) => false,
+            (_, event::Event::Pointer(pointer)) => match pointer.action {
+                event::PointerAction::Move => match self.view.drag {
+                    Some(drag) => {
+                        let limit = self.limit(|state| context.size_of(state));
+                        let offset = layout::Point {
+                            x: drag.offset.x + (drag.position.x - pointer.position.x) as f32,
+                            y: drag.offset.y + (drag.position.y - pointer.position.y) as f32,
+                        };
+                        self.scroll_to(offset, limit);
+                        true
+                    }
+                    None => false,
+                },
+                _ => self.view.drag.take().is_some(),
+            },
+            (
+                _,
+                event::Event::Gesture(event::Gesture::Swipe {
+                    direction,
+                    velocity,
+                    ..
+                }),
+            ) => {
+                // Swiping up moves the content up, which scrolls further down
+                let velocity = match direction {
+                    event::Direction::Up if self.direction.vertical() => layout::Point {
+                        x: 0.0,
+                        y: *velocity,
+                    },
+                    event::Direction::Down if self.direction.vertical() => layout::Point {
+                        x: 0.0,
+                        y: -velocity,
+                    },
+                    event::Direction::Left if self.direction.horizontal() => layout::Point {
+                        x: *velocity,
+                        y: 0.0,
+                    },
+                    event::Direction::Right if self.direction.horizontal() => layout::Point {
+                        x: -velocity,
+                        y: 0.0,
+                    },
+                    _ => return false,
+                };
+                let time = context.now;
+                self.view.fling = Some(Fling { velocity, time });
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a, N, T, M> widget::Node<'a, T, M> for Viewport<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, 1);
+        visitor.accept_child(&self.content);
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let mut visitor = visitor.accept_node_mut(self, 1);
+        visitor.accept_child_mut(&mut self.content);
+        visitor.end()
+    }
+}
+
+impl<'a, N, T, M> widget::Widget<'a, T, M> for Viewport<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        layout::Style {
+            flex_grow: 1.0,
+            flex_shrink: 1.0,
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+
+    fn scroll_offset(&self) -> layout::Point {
+        self.offset
+    }
+
+    fn clips_children(&self) -> bool {
+        true
+    }
+}
+
+impl<'a, N, T, M> widget::Node<'a, T, M> for Content<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, 1);
+        visitor.accept_child(&self.child);
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let mut visitor = visitor.accept_node_mut(self, 1);
+        visitor.accept_child_mut(&mut self.child);
+        visitor.end()
+    }
+}
+
+impl<'a, N, T, M> widget::Widget<'a, T, M> for Content<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        // Being absolutely positioned, the content doesn't stretch the viewport to fit it
+        let full = |fixed: bool| {
+            if fixed {
+                stretch::style::Dimension::Percent(1.0)
+            } else {
+                stretch::style::Dimension::Auto
+            }
+        };
+
+        layout::Style {
+            position_type: stretch::style::PositionType::Absolute,
+            position: stretch::geometry::Rect {
+                start: stretch::style::Dimension::Points(0.0),
+                top: stretch::style::Dimension::Points(0.0),
+                ..Default::default()
+            },
+            size: stretch::geometry::Size {
+                width: full(!self.direction.horizontal()),
+                height: full(!self.direction.vertical()),
+            },
+            min_size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Percent(1.0),
+                height: stretch::style::Dimension::Percent(1.0),
+            },
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_items: stretch::style::AlignItems::Stretch,
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+}
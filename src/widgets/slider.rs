@@ -0,0 +1,439 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use embedded_graphics::egrectangle;
+
+const THICKNESS: i32 = 12;
+const THUMB_LENGTH: i32 = 6;
+const TICK_LENGTH: i32 = 3;
+/// The minimum distance between tick marks, closer than which only every so many are drawn.
+const TICK_SPACING: i32 = 4;
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    pressed: bool,
+    editing: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// The value increases from left to right.
+    Horizontal,
+    /// The value increases from bottom to top.
+    Vertical,
+}
+
+/// A bar with a thumb that can be dragged to pick a value between 0 and `max`.
+///
+/// Like a progress bar, the value is owned by the application, which should update it in response
+/// to the message emitted when the slider is moved.  The arrow keys along the slider change the
+/// value by one step, as do `Next` and `Previous` in the editing mode that `Activate` toggles; see
+/// `event::Navigation`.
+#[derive(Debug)]
+pub struct Slider<'a, M> {
+    state: &'a mut State,
+    value: u32,
+    max: u32,
+    step: u32,
+    tick_marks: bool,
+    orientation: Orientation,
+    on_change: Option<fn(u32) -> M>,
+}
+
+/// The pixels of the tick marks of a slider.
+#[derive(Debug)]
+pub struct Ticks<C> {
+    axis: Axis,
+    color: C,
+    step: u32,
+    max: u32,
+    count: u32,
+    tick: u32,
+    pixel: i32,
+}
+
+/// Maps between positions along and across a slider and display coordinates.
+#[derive(Clone, Copy, Debug)]
+struct Axis {
+    orientation: Orientation,
+    origin: embedded_graphics::geometry::Point,
+    length: i32,
+    breadth: i32,
+}
+
+impl<'a, M> Slider<'a, M> {
+    pub fn new(state: &'a mut State, value: u32, max: u32) -> Self {
+        let step = 1;
+        let tick_marks = false;
+        let orientation = Orientation::Horizontal;
+        let on_change = None;
+        Self {
+            state,
+            value,
+            max,
+            step,
+            tick_marks,
+            orientation,
+            on_change,
+        }
+    }
+
+    /// Makes the slider snap to multiples of `step`; defaults to 1.
+    pub fn step(mut self, step: u32) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Draws a tick mark at every step, or at every so many steps if they would be too close
+    /// together.
+    pub fn tick_marks(mut self, tick_marks: bool) -> Self {
+        self.tick_marks = tick_marks;
+        self
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Sets the function that creates the message emitted with the new value when the slider is
+    /// moved.
+    pub fn on_change(mut self, on_change: fn(u32) -> M) -> Self {
+        self.on_change = Some(on_change);
+        self
+    }
+
+    fn snap(&self, value: u32) -> u32 {
+        let snapped = value.saturating_add(self.step / 2) / self.step * self.step;
+        snapped.min(self.max)
+    }
+
+    fn change<T>(&self, context: &mut widget::EventContext<T, M>, value: u32) -> bool {
+        if value == self.value {
+            return false;
+        }
+        if let Some(on_change) = self.on_change {
+            context.emit(on_change(value));
+        }
+        true
+    }
+
+    fn drag<T>(
+        &self,
+        context: &mut widget::EventContext<T, M>,
+        position: embedded_graphics::geometry::Point,
+    ) {
+        let axis = Axis::new(self.orientation, context.position, context.size);
+        let usable = axis.usable();
+        let along = (axis.along(position) - THUMB_LENGTH / 2).max(0).min(usable) as u32;
+        let value = if usable > 0 {
+            let (along, max, usable) = (u64::from(along), u64::from(self.max), usable as u64);
+            ((along * max + usable / 2) / usable) as u32
+        } else {
+            0
+        };
+        self.change(context, self.snap(value));
+    }
+
+    fn adjust<T>(&self, context: &mut widget::EventContext<T, M>, increase: bool) -> bool {
+        let value = if increase {
+            self.value.saturating_add(self.step).min(self.max)
+        } else {
+            self.value.saturating_sub(self.step)
+        };
+        self.change(context, value)
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Slider<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Slider<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<
+            core::iter::Chain<util::RectIter<T::Color>, util::RectIter<T::Color>>,
+            util::RectIter<T::Color>,
+        >,
+        Ticks<T::Color>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let theme = context.theme;
+        let axis = Axis::new(self.orientation, context.position, context.size);
+
+        // The thumb spans the slider except for the tick marks, with the track in its middle
+        let band = axis.breadth - TICK_LENGTH - 1;
+        let middle = band / 2;
+        let thumb = axis.offset(self.value.min(self.max), self.max);
+
+        let track = axis.rect(0, middle - 1, axis.length - 1, middle + 1);
+        let filled = axis.rect(0, middle - 1, thumb + THUMB_LENGTH / 2, middle + 1);
+        let thumb = axis.rect(thumb, 0, thumb + THUMB_LENGTH - 1, band - 1);
+
+        let thumb_fill = if self.state.editing && context.focused {
+            theme.pressed_color()
+        } else if context.hovered {
+            theme.hover_color()
+        } else {
+            theme.background_color()
+        };
+
+        // Doubling the step between ticks also bounds the number of ticks for large ranges
+        let mut step = self.step;
+        while step < self.max && axis.offset(step, self.max) < TICK_SPACING {
+            step = step.saturating_mul(2);
+        }
+        let count = if self.tick_marks {
+            self.max / step + 1
+        } else {
+            0
+        };
+        let ticks = Ticks {
+            axis,
+            color: theme.border_color(),
+            step,
+            max: self.max,
+            count,
+            tick: 0,
+            pixel: 0,
+        };
+
+        egrectangle!(track.0, track.1, stroke = Some(theme.border_color()))
+            .into_iter()
+            .chain(egrectangle!(
+                filled.0,
+                filled.1,
+                fill = Some(theme.fill_color())
+            ))
+            .chain(egrectangle!(
+                thumb.0,
+                thumb.1,
+                fill = Some(thumb_fill),
+                stroke = Some(theme.border_color())
+            ))
+            .chain(ticks)
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        let thickness = stretch::style::Dimension::Points(THICKNESS as f32);
+        let min_length = stretch::style::Dimension::Points(20.0);
+        let (size, min_size) = match self.orientation {
+            Orientation::Horizontal => (
+                stretch::geometry::Size {
+                    width: stretch::style::Dimension::Auto,
+                    height: thickness,
+                },
+                stretch::geometry::Size {
+                    width: min_length,
+                    height: thickness,
+                },
+            ),
+            Orientation::Vertical => (
+                stretch::geometry::Size {
+                    width: thickness,
+                    height: stretch::style::Dimension::Auto,
+                },
+                stretch::geometry::Size {
+                    width: thickness,
+                    height: min_length,
+                },
+            ),
+        };
+
+        layout::Style {
+            flex_shrink: 1.0,
+            flex_grow: 1.0,
+            flex_basis: stretch::style::Dimension::Auto,
+            align_self: stretch::style::AlignSelf::Stretch,
+            size,
+            min_size,
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        match event {
+            event::Event::Pointer(pointer) => match pointer.action {
+                event::PointerAction::Down => {
+                    self.state.pressed = true;
+                    self.drag(context, pointer.position);
+                    true
+                }
+                event::PointerAction::Move if self.state.pressed => {
+                    self.drag(context, pointer.position);
+                    true
+                }
+                event::PointerAction::Move => false,
+                event::PointerAction::Up => {
+                    if self.state.pressed {
+                        self.drag(context, pointer.position);
+                    }
+                    self.state.pressed = false;
+                    true
+                }
+            },
+            event::Event::Navigation(navigation) => {
+                let (decrease, increase) = match self.orientation {
+                    Orientation::Horizontal => (event::Navigation::Left, event::Navigation::Right),
+                    Orientation::Vertical => (event::Navigation::Down, event::Navigation::Up),
+                };
+
+                match *navigation {
+                    event::Navigation::Activate => {
+                        self.state.editing = !self.state.editing;
+                        true
+                    }
+                    event::Navigation::Next if self.state.editing => {
+                        self.adjust(context, true);
+                        true
+                    }
+                    event::Navigation::Previous if self.state.editing => {
+                        self.adjust(context, false);
+                        true
+                    }
+                    // Past 0 and `max`, the arrows move the focus away from the slider
+                    navigation if navigation == increase => self.adjust(context, true),
+                    navigation if navigation == decrease => self.adjust(context, false),
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<C> Iterator for Ticks<C>
+where
+    C: embedded_graphics::pixelcolor::PixelColor,
+{
+    type Item = embedded_graphics::drawable::Pixel<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.tick >= self.count {
+            return None;
+        }
+
+        let value = (self.tick * self.step).min(self.max);
+        let along = self.axis.offset(value, self.max) + THUMB_LENGTH / 2;
+        let across = self.axis.breadth - TICK_LENGTH + self.pixel;
+        let point = self.axis.point(along, across);
+
+        self.pixel += 1;
+        if self.pixel == TICK_LENGTH {
+            self.pixel = 0;
+            self.tick += 1;
+        }
+
+        Some(embedded_graphics::drawable::Pixel(point, self.color))
+    }
+}
+
+impl Axis {
+    fn new(
+        orientation: Orientation,
+        origin: embedded_graphics::geometry::Point,
+        size: embedded_graphics::geometry::Size,
+    ) -> Self {
+        let (length, breadth) = match orientation {
+            Orientation::Horizontal => (size.width as i32, size.height as i32),
+            Orientation::Vertical => (size.height as i32, size.width as i32),
+        };
+        Self {
+            orientation,
+            origin,
+            length,
+            breadth,
+        }
+    }
+
+    /// The distance the thumb can travel.
+    fn usable(&self) -> i32 {
+        (self.length - THUMB_LENGTH).max(0)
+    }
+
+    /// The position of the thumb for `value`.
+    fn offset(&self, value: u32, max: u32) -> i32 {
+        if max == 0 {
+            0
+        } else {
+            (i64::from(self.usable()) * i64::from(value) / i64::from(max)) as i32
+        }
+    }
+
+    fn point(&self, along: i32, across: i32) -> embedded_graphics::geometry::Point {
+        match self.orientation {
+            Orientation::Horizontal => embedded_graphics::geometry::Point::new(
+                self.origin.x + along,
+                self.origin.y + across,
+            ),
+            Orientation::Vertical => embedded_graphics::geometry::Point::new(
+                self.origin.x + across,
+                self.origin.y + self.length - 1 - along,
+            ),
+        }
+    }
+
+    fn along(&self, point: embedded_graphics::geometry::Point) -> i32 {
+        match self.orientation {
+            Orientation::Horizontal => point.x - self.origin.x,
+            Orientation::Vertical => self.origin.y + self.length - 1 - point.y,
+        }
+    }
+
+    /// The top left and bottom right corners of a rectangle between two points.
+    fn rect(
+        &self,
+        along1: i32,
+        across1: i32,
+        along2: i32,
+        across2: i32,
+    ) -> (
+        embedded_graphics::geometry::Point,
+        embedded_graphics::geometry::Point,
+    ) {
+        let a = self.point(along1, across1);
+        let b = self.point(along2, across2);
+        (
+            embedded_graphics::geometry::Point::new(a.x.min(b.x), a.y.min(b.y)),
+            embedded_graphics::geometry::Point::new(a.x.max(b.x), a.y.max(b.y)),
+        )
+    }
+}
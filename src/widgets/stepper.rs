@@ -0,0 +1,367 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::time;
+use embedded_graphics::egrectangle;
+
+/// The capacity of the buffer that the value is formatted into, which fits any `i32`.
+pub type Capacity = heapless::consts::U12;
+
+/// Events that follow each other at most this far apart count as a key or button being held.
+const REPEAT_WINDOW: time::Duration = time::Duration::from_millis(300);
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    text: heapless::String<Capacity>,
+    pressed: Option<Part>,
+    editing: bool,
+    hold: Option<Hold>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    Decrease,
+    Increase,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Hold {
+    part: Part,
+    since: time::Duration,
+    last: time::Duration,
+}
+
+/// A number between `-` and `+` buttons, for entering values between a minimum and a maximum.
+///
+/// The value is owned by the application, which should update it in response to the message
+/// emitted when the value is changed.  It is displayed with a fixed number of decimals, so e.g. a
+/// temperature in tenths of a degree can be shown as `21.5`.
+///
+/// The up and right arrows increase the value by one step and the down and left arrows decrease
+/// it, as do `Next` and `Previous` in editing mode; see `event::Navigation`.  When a key is held
+/// down, i.e. its events are repeated, the step grows tenfold after one second and a hundredfold
+/// after three.  On touch panels, a long press on one of the buttons steps once more, and if the
+/// gesture recognizer is configured to repeat long presses, e.g. with
+/// `gesture::Config::long_press_repeat` passed to `app::Runner::gesture_config`, holding the button
+/// keeps stepping and speeds up the same way.
+#[derive(Debug)]
+pub struct Stepper<'a, M> {
+    state: &'a mut State,
+    value: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+    decimals: u8,
+    on_change: Option<fn(i32) -> M>,
+}
+
+impl<'a, M> Stepper<'a, M> {
+    pub fn new(state: &'a mut State, value: i32, min: i32, max: i32) -> Self {
+        let step = 1;
+        let decimals = 0;
+        let on_change = None;
+        state.text = format(value, decimals);
+        Self {
+            state,
+            value,
+            min,
+            max,
+            step,
+            decimals,
+            on_change,
+        }
+    }
+
+    /// Sets by how much the value changes per step; defaults to 1.
+    pub fn step(mut self, step: i32) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Displays the value divided by `10^decimals`, with that many digits after the decimal point;
+    /// at most 9.
+    pub fn decimals(mut self, decimals: u8) -> Self {
+        self.decimals = decimals.min(9);
+        self.state.text = format(self.value, self.decimals);
+        self
+    }
+
+    /// Sets the function that creates the message emitted with the new value when it is changed.
+    pub fn on_change(mut self, on_change: fn(i32) -> M) -> Self {
+        self.on_change = Some(on_change);
+        self
+    }
+
+    /// How many steps a navigation or long press event changes the value by, depending on how long
+    /// it has been repeated.
+    fn acceleration(&mut self, part: Part, now: time::Duration) -> i64 {
+        let since = match self.state.hold {
+            Some(hold) if hold.part == part && now <= hold.last + REPEAT_WINDOW => hold.since,
+            _ => now,
+        };
+        self.state.hold = Some(Hold {
+            part,
+            since,
+            last: now,
+        });
+
+        let held = now.checked_sub(since).unwrap_or_default();
+        if held >= time::Duration::from_secs(3) {
+            100
+        } else if held >= time::Duration::from_secs(1) {
+            10
+        } else {
+            1
+        }
+    }
+
+    fn adjust<T>(&self, context: &mut widget::EventContext<T, M>, part: Part, steps: i64) -> bool {
+        let delta = i64::from(self.step) * steps;
+        let value = match part {
+            Part::Decrease => i64::from(self.value) - delta,
+            Part::Increase => i64::from(self.value) + delta,
+        };
+        let value = value.max(i64::from(self.min)).min(i64::from(self.max)) as i32;
+
+        if value == self.value {
+            return false;
+        }
+        if let Some(on_change) = self.on_change {
+            context.emit(on_change(value));
+        }
+        true
+    }
+
+    fn repeat<T>(&mut self, context: &mut widget::EventContext<T, M>, part: Part) -> bool {
+        let steps = self.acceleration(part, context.now);
+        self.adjust(context, part, steps)
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Stepper<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Stepper<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<
+            core::iter::Chain<
+                core::iter::Chain<util::RectIter<T::Color>, util::FontIter<'a, T>>,
+                util::RectIter<T::Color>,
+            >,
+            util::FontIter<'a, T>,
+        >,
+        util::Glyphs<'a, T, Capacity>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let theme = context.theme;
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let side = context.size.height as i32;
+
+        let glyph_size = T::Font::render_str("0").size();
+        let advance = glyph_size.width as i32;
+        let yt = y1 + (side - glyph_size.height as i32) / 2;
+
+        let button = |part: Part, x: i32, label: &'static str| {
+            let pressed = self.state.pressed == Some(part);
+            let (fill, text_color) = if pressed {
+                (Some(theme.pressed_color()), theme.background_color())
+            } else {
+                (None, theme.text_color())
+            };
+            let xt = x + (side - advance) / 2;
+            let rect = egrectangle!(
+                (x, y1),
+                (x + side - 1, y2),
+                fill = fill,
+                stroke = Some(theme.border_color())
+            );
+            let text = T::Font::render_str(label)
+                .translate(embedded_graphics::geometry::Point::new(xt, yt))
+                .stroke(Some(text_color));
+            (rect.into_iter(), text.into_iter())
+        };
+        let (decrease_rect, decrease_text) = button(Part::Decrease, x1, "-");
+        let (increase_rect, increase_text) = button(Part::Increase, x2 - side + 1, "+");
+
+        let text = self.state.text.clone();
+        let width = text.len() as i32 * advance;
+        let xt = x1 + (context.size.width as i32 - width) / 2;
+        let color = if self.state.editing && context.focused {
+            theme.focus_color()
+        } else {
+            theme.text_color()
+        };
+        let position = embedded_graphics::geometry::Point::new(xt, yt);
+        let glyphs = util::Glyphs::new(text, position, advance, color);
+
+        decrease_rect
+            .chain(decrease_text)
+            .chain(increase_rect)
+            .chain(increase_text)
+            .chain(glyphs)
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        // Leave room for the widest value, so that the stepper doesn't change size
+        let glyph_size = T::Font::render_str("0").size();
+        let chars = format(self.min, self.decimals)
+            .len()
+            .max(format(self.max, self.decimals).len());
+        let side = glyph_size.height as f32 + 2.0 * theme.spacing();
+        let width = 2.0 * side + (chars as f32 + 2.0) * glyph_size.width as f32;
+
+        layout::Style {
+            align_self: stretch::style::AlignSelf::FlexStart,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(width),
+                height: stretch::style::Dimension::Points(side),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        match event {
+            event::Event::Pointer(pointer) => match pointer.action {
+                event::PointerAction::Down => {
+                    let part = part(context.position, context.size, pointer.position);
+                    self.state.pressed = part;
+                    if let Some(part) = part {
+                        self.adjust(context, part, 1);
+                    }
+                    true
+                }
+                event::PointerAction::Up => {
+                    self.state.pressed = None;
+                    true
+                }
+                event::PointerAction::Move => false,
+            },
+            event::Event::Gesture(event::Gesture::LongPress { position }) => {
+                match part(context.position, context.size, *position) {
+                    Some(part) => {
+                        self.repeat(context, part);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            event::Event::Navigation(navigation) => match *navigation {
+                event::Navigation::Activate => {
+                    self.state.editing = !self.state.editing;
+                    true
+                }
+                event::Navigation::Next if self.state.editing => {
+                    self.repeat(context, Part::Increase);
+                    true
+                }
+                event::Navigation::Previous if self.state.editing => {
+                    self.repeat(context, Part::Decrease);
+                    true
+                }
+                // At the minimum and maximum, the arrows leave the stepper instead
+                event::Navigation::Right | event::Navigation::Up => {
+                    self.repeat(context, Part::Increase)
+                }
+                event::Navigation::Left | event::Navigation::Down => {
+                    self.repeat(context, Part::Decrease)
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Which of the buttons of a stepper at `position` with size `size` is at `point`, if any.
+fn part(
+    position: embedded_graphics::geometry::Point,
+    size: embedded_graphics::geometry::Size,
+    point: embedded_graphics::geometry::Point,
+) -> Option<Part> {
+    let side = size.height as i32;
+    let x = point.x - position.x;
+    if x < side {
+        Some(Part::Decrease)
+    } else if x >= size.width as i32 - side {
+        Some(Part::Increase)
+    } else {
+        None
+    }
+}
+
+/// Formats `value` divided by `10^decimals`, without allocating.
+fn format(value: i32, decimals: u8) -> heapless::String<Capacity> {
+    use core::fmt::Write;
+
+    let mut text = heapless::String::new();
+    let value = i64::from(value);
+    // The capacity fits the longest possible value, so writing can't fail
+    let _ = if decimals == 0 {
+        write!(text, "{}", value)
+    } else {
+        let scale = 10_i64.pow(u32::from(decimals));
+        let sign = if value < 0 { "-" } else { "" };
+        let value = value.abs();
+        let width = usize::from(decimals);
+        write!(
+            text,
+            "{}{}.{:0width$}",
+            sign,
+            value / scale,
+            value % scale,
+            width = width
+        )
+    };
+    text
+}
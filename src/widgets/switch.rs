@@ -0,0 +1,187 @@
+//! Labelled widgets that are switched on and off, such as checkboxes and toggles.
+//!
+//! A switch shows an indicator of its state next to its label, and is toggled by tapping it or by
+//! activating it while it is focused.  Only the indicator differs between kinds of switches; see
+//! `Indicator`.
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::marker;
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    pressed: bool,
+}
+
+/// How a switch shows whether it is on.
+pub trait Indicator<'a, T>
+where
+    T: theme::Theme<'a>,
+{
+    type Output: Iterator<Item = embedded_graphics::drawable::Pixel<T::Color>>;
+
+    /// The width of the indicator, in multiples of its height, which is that of the label.
+    fn width() -> i32;
+
+    /// Draws the indicator `side` pixels high, with its top left corner at `top_left`.
+    fn draw(
+        on: bool,
+        context: &widget::DrawContext<T>,
+        top_left: embedded_graphics::geometry::Point,
+        side: i32,
+    ) -> Self::Output;
+}
+
+/// A labelled widget that is on or off, with the indicator `I`.
+///
+/// The state is owned by the application, which should update it in response to the message
+/// emitted when the switch is toggled.
+#[derive(Debug)]
+pub struct Switch<'a, M, I> {
+    state: &'a mut State,
+    label: &'a str,
+    on: bool,
+    on_toggle: Option<fn(bool) -> M>,
+    indicator: marker::PhantomData<I>,
+}
+
+impl<'a, M, I> Switch<'a, M, I> {
+    pub fn new(state: &'a mut State, label: &'a str, on: bool) -> Self {
+        let on_toggle = None;
+        let indicator = marker::PhantomData;
+        Self {
+            state,
+            label,
+            on,
+            on_toggle,
+            indicator,
+        }
+    }
+
+    /// Sets the function that creates the message emitted with the new state when the switch is
+    /// toggled.
+    pub fn on_toggle(mut self, on_toggle: fn(bool) -> M) -> Self {
+        self.on_toggle = Some(on_toggle);
+        self
+    }
+
+    fn toggle<T>(&self, context: &mut widget::EventContext<T, M>) {
+        if let Some(on_toggle) = self.on_toggle {
+            context.emit(on_toggle(!self.on));
+        }
+    }
+}
+
+impl<'a, T, M, I> widget::Node<'a, T, M> for Switch<'a, M, I>
+where
+    T: theme::Theme<'a>,
+    I: Indicator<'a, T>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, T, M, I> widget::Widget<'a, T, M> for Switch<'a, M, I>
+where
+    T: theme::Theme<'a>,
+    I: Indicator<'a, T>,
+{
+    type Output = core::iter::Chain<I::Output, util::FontIter<'a, T>>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        let theme = context.theme;
+        let text = T::Font::render_str(&self.label);
+        let text_size = text.size();
+
+        // The indicator is as high as the label
+        let side = text_size.height as i32;
+        let x = context.position.x;
+        let y = context.position.y + (context.size.height as i32 - side) / 2;
+        let top_left = embedded_graphics::geometry::Point::new(x, y);
+        let indicator_iter = I::draw(self.on, context, top_left, side);
+
+        let xt = x + I::width() * side + theme.spacing() as i32;
+        let text_iter = text
+            .translate(embedded_graphics::geometry::Point::new(xt, y))
+            .stroke(Some(theme.text_color()))
+            .into_iter();
+
+        indicator_iter.chain(text_iter)
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let size = T::Font::render_str(&self.label).size();
+        let indicator = I::width() as f32 * size.height as f32;
+        let width = indicator + theme.spacing() + size.width as f32;
+        layout::Style {
+            align_self: stretch::style::AlignSelf::FlexStart,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(width),
+                height: stretch::style::Dimension::Points(size.height as f32),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        match event {
+            event::Event::Pointer(pointer) => match pointer.action {
+                event::PointerAction::Down => {
+                    self.state.pressed = true;
+                    true
+                }
+                event::PointerAction::Up => {
+                    if self.state.pressed && context.contains(pointer.position) {
+                        self.toggle(context);
+                    }
+                    self.state.pressed = false;
+                    true
+                }
+                event::PointerAction::Move => false,
+            },
+            event::Event::Navigation(event::Navigation::Activate) => {
+                self.toggle(context);
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,601 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use crate::widgets::rows;
+use core::borrow::Borrow;
+use core::borrow::BorrowMut;
+use embedded_graphics::egrectangle;
+
+/// The maximum number of columns of a table.
+pub type MaxColumns = heapless::consts::U8;
+
+/// The maximum length of the text of a cell.
+pub type CellCapacity = heapless::consts::U32;
+
+/// The text of a cell, as written by a `Source`.
+pub type Cell = heapless::String<CellCapacity>;
+
+/// The rows of a table, which are formatted one cell at a time while the table is drawn, so that
+/// no row needs to be stored as text.
+pub trait Source {
+    /// The number of rows.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes the text of the cell in `column` of `row` to `text`, e.g. using `write!`.
+    ///
+    /// Text that does not fit in `CellCapacity` is dropped, and text that does not fit in the
+    /// column is cut off.
+    fn cell(&self, row: usize, column: usize, text: &mut Cell);
+}
+
+/// How the width of a column is determined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Width {
+    /// A width in pixels.
+    Fixed(f32),
+    /// A share of the width that is left after the fixed width columns, relative to the other
+    /// flexible columns.
+    Flex(f32),
+}
+
+/// How text is aligned within a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+/// A column of a table.
+#[derive(Clone, Copy, Debug)]
+pub struct Column<'a> {
+    pub title: &'a str,
+    pub width: Width,
+    pub align: Align,
+}
+
+/// The state of a table.
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    header: layout::State,
+    rows: rows::Rows,
+}
+
+/// A grid of text with a header row, showing the rows of a `Source`.
+///
+/// Only the rows that fit in the height of the table are drawn, and the table grows to fill the
+/// space available in its parent.  When there are more rows than fit, a scrollbar is shown, and the
+/// rows scroll by whole rows when the pointer is dragged over them.  Every other row is filled with
+/// the stripe color of the theme.
+///
+/// Tapping a row selects it.  While the table has the focus, the up and down arrows highlight the
+/// row above or below, scrolling the table along with it.  With an encoder, the first `Activate`
+/// lets `Next` and `Previous` move the highlight, and the second one selects the highlighted row;
+/// see `event::Navigation`.
+#[derive(Debug)]
+pub struct Table<'a, S, M>
+where
+    S: ?Sized,
+{
+    state: &'a mut State,
+    source: &'a S,
+    columns: &'a [Column<'a>],
+    on_select: Option<fn(usize) -> M>,
+}
+
+/// The parts of the rows of a table, which formats its cells as they are drawn.
+pub struct Cells<'a, T, S>
+where
+    T: theme::Theme<'a>,
+    S: ?Sized,
+{
+    source: &'a S,
+    columns: &'a [Column<'a>],
+    bounds: heapless::Vec<(i32, i32), MaxColumns>,
+    grid: Grid,
+    text_color: T::Color,
+    stripe_color: T::Color,
+    rows: core::ops::Range<usize>,
+    line: usize,
+    step: usize,
+}
+
+/// The header row of a table, which is laid out as a child of the table so that the number of rows
+/// that fit below it follows from the layout, but drawn by the table.
+struct Header<L> {
+    layout: L,
+}
+
+/// The dimensions of the rows of a table, in pixels.
+#[derive(Clone, Copy, Debug)]
+struct Grid {
+    x1: i32,
+    x2: i32,
+    y1: i32,
+    row_height: i32,
+    padding: i32,
+    advance: i32,
+}
+
+impl<'a> Column<'a> {
+    /// Creates a column that shares the available width with the other flexible columns, and
+    /// whose text is aligned at the start.
+    pub fn new(title: &'a str) -> Self {
+        let width = Width::Flex(1.0);
+        let align = Align::Start;
+        Self {
+            title,
+            width,
+            align,
+        }
+    }
+
+    pub fn width(mut self, width: Width) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+}
+
+impl<'s> Source for [&'s [&'s str]] {
+    fn len(&self) -> usize {
+        <[_]>::len(self)
+    }
+
+    fn cell(&self, row: usize, column: usize, text: &mut Cell) {
+        if let Some(cell) = self[row].get(column) {
+            util::push_truncated(text, cell);
+        }
+    }
+}
+
+impl State {
+    /// The index of the first visible row.
+    pub fn first(&self) -> usize {
+        self.rows.first
+    }
+
+    /// The index of the selected row, if any.
+    pub fn selected(&self) -> Option<usize> {
+        self.rows.highlighted
+    }
+
+    pub fn select(&mut self, selected: Option<usize>) {
+        self.rows.highlighted = selected;
+    }
+
+    /// Scrolls the table as little as possible to make the row at `index` visible.
+    pub fn scroll_to(&mut self, index: usize) {
+        self.rows.scroll_to(index);
+    }
+}
+
+impl<'a, S, M> Table<'a, S, M>
+where
+    S: Source + ?Sized,
+{
+    /// Creates a table of the rows of `source`; only the first `MaxColumns` of `columns` are shown.
+    pub fn new(state: &'a mut State, source: &'a S, columns: &'a [Column<'a>]) -> Self {
+        state.rows.set_len(source.len());
+
+        let on_select = None;
+        Self {
+            state,
+            source,
+            columns,
+            on_select,
+        }
+    }
+
+    /// Sets the function that creates the message emitted with the index of a row when it is
+    /// selected.
+    pub fn on_select(mut self, on_select: fn(usize) -> M) -> Self {
+        self.on_select = Some(on_select);
+        self
+    }
+
+    fn select<T>(&mut self, context: &mut widget::EventContext<T, M>, index: usize) {
+        self.state.rows.highlighted = Some(index);
+        if let Some(on_select) = self.on_select {
+            context.emit(on_select(index));
+        }
+    }
+}
+
+impl<'a, S, T, M> widget::Node<'a, T, M> for Table<'a, S, M>
+where
+    S: Source + ?Sized,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let mut visitor = visitor.accept_node(self, 1);
+        visitor.accept_child(&Header {
+            layout: self.state.header.clone(),
+        });
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let mut visitor = visitor.accept_node_mut(self, 1);
+        visitor.accept_child_mut(&mut Header {
+            layout: &mut self.state.header,
+        });
+        visitor.end()
+    }
+}
+
+impl<'a, S, T, M> widget::Widget<'a, T, M> for Table<'a, S, M>
+where
+    S: Source + ?Sized,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        util::Pixels<'a, T, Cells<'a, T, S>>,
+        core::iter::Chain<
+            core::iter::Chain<util::RectIter<T::Color>, util::RectIter<T::Color>>,
+            core::iter::Chain<
+                util::OptionalIter<util::RectIter<T::Color>>,
+                util::OptionalIter<util::RectIter<T::Color>>,
+            >,
+        >,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let theme = context.theme;
+        let state = &self.state.rows;
+        let glyph_size = T::Font::render_str("0").size();
+        let row_height = row_height(theme, glyph_size.height);
+        let range = state.range();
+
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let scrollbar_width = if state.len > state.visible {
+            theme.scrollbar_width() as i32
+        } else {
+            0
+        };
+        let grid = Grid {
+            x1: x1 + 1,
+            x2: x2 - 1 - scrollbar_width,
+            y1: y1 + 1,
+            row_height,
+            padding: theme.spacing() as i32,
+            advance: glyph_size.width as i32,
+        };
+
+        // Fixed width columns get their width first, and flexible ones share what is left
+        let inner = (grid.x2 - grid.x1 + 1).max(0) as f32;
+        let (fixed, flex) = self
+            .columns
+            .iter()
+            .fold((0.0, 0.0), |(fixed, flex), column| match column.width {
+                Width::Fixed(width) => (fixed + width, flex),
+                Width::Flex(share) => (fixed, flex + share),
+            });
+        let remaining = (inner - fixed).max(0.0);
+        let mut x = grid.x1 as f32;
+        let mut bounds = heapless::Vec::new();
+        for column in self.columns {
+            let width = match column.width {
+                Width::Fixed(width) => width,
+                Width::Flex(share) if flex > 0.0 => remaining * share / flex,
+                Width::Flex(_) => 0.0,
+            };
+            let start = x as i32;
+            x += width;
+            if bounds.push((start, x as i32 - start)).is_err() {
+                break;
+            }
+        }
+
+        let cells = Cells {
+            source: self.source,
+            columns: self.columns,
+            bounds,
+            grid,
+            text_color: theme.text_color(),
+            stripe_color: theme.stripe_color(),
+            rows: range.clone(),
+            line: 0,
+            step: 0,
+        };
+
+        let border = egrectangle!((x1, y1), (x2, y2), stroke = Some(theme.border_color()));
+        let header_y = grid.y1 + row_height;
+        let separator = egrectangle!(
+            (grid.x1, header_y),
+            (x2 - 1, header_y),
+            stroke = Some(theme.border_color())
+        );
+
+        // The selection is an outline, so that it doesn't hide the row, also on monochrome displays
+        let selection = state
+            .highlighted
+            .filter(|index| range.contains(index))
+            .map(|index| {
+                let color = if state.editing && context.focused {
+                    theme.focus_color()
+                } else {
+                    theme.fill_color()
+                };
+                let y = grid.row_y(1 + index - range.start);
+                egrectangle!(
+                    (grid.x1, y),
+                    (grid.x2, y + row_height - 1),
+                    stroke = Some(color)
+                )
+                .into_iter()
+            });
+
+        let scrollbar = state.scrollbar(x2, header_y + 1, y2, scrollbar_width, theme.fill_color());
+
+        util::Pixels::new(cells).chain(
+            border.into_iter().chain(separator.into_iter()).chain(
+                selection
+                    .into_iter()
+                    .flatten()
+                    .chain(scrollbar.into_iter().flatten()),
+            ),
+        )
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        // At least the header and a single row
+        let glyph_height = T::Font::render_str("0").size().height;
+        let height = 2 * row_height(theme, glyph_height) + 3;
+
+        let border = stretch::style::Dimension::Points(1.0);
+
+        layout::Style {
+            flex_grow: 1.0,
+            flex_shrink: 1.0,
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_self: stretch::style::AlignSelf::Stretch,
+            align_items: stretch::style::AlignItems::Stretch,
+            min_size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height: stretch::style::Dimension::Points(height as f32),
+            },
+            padding: stretch::geometry::Rect {
+                start: border,
+                end: border,
+                top: border,
+                bottom: border,
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.state.rows.len > 0
+    }
+
+    fn animate(&mut self, context: &widget::AnimationContext) {
+        // The header is laid out as high as a row
+        let row_height = context
+            .size_of(&self.state.header)
+            .map_or(0, |size| size.height as i32);
+        self.state.rows.visible = visible_rows(context.size, row_height);
+        self.state.rows.clamp();
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let glyph_height = T::Font::render_str("0").size().height;
+        let row_height = row_height(context.theme, glyph_height);
+        // Rows start below the border, the header and the line under it
+        let top = context.position.y + 2 + row_height;
+        let state = &mut self.state.rows;
+        let outcome = match (context.phase, event) {
+            (event::Phase::Capture, _) => rows::Outcome::Ignored,
+            (_, event::Event::Pointer(pointer)) => state.drag(context, pointer, top, row_height),
+            (event::Phase::Target, event::Event::Navigation(navigation)) => {
+                state.navigate(*navigation)
+            }
+            _ => rows::Outcome::Ignored,
+        };
+
+        match outcome {
+            rows::Outcome::Ignored => false,
+            rows::Outcome::Handled => true,
+            rows::Outcome::Chosen(index) => {
+                self.select(context, index);
+                true
+            }
+        }
+    }
+}
+
+impl<'a, L, T, M> widget::Node<'a, T, M> for Header<L>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, L, T, M> widget::Widget<'a, T, M> for Header<L>
+where
+    L: Borrow<layout::State> + BorrowMut<layout::State>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let glyph_height = T::Font::render_str("0").size().height;
+        let height = row_height(theme, glyph_height);
+        layout::Style {
+            flex_shrink: 0.0,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height: stretch::style::Dimension::Points(height as f32),
+            },
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        self.layout.borrow()
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        self.layout.borrow_mut()
+    }
+}
+
+impl Grid {
+    /// The top of a line of the table, where the header is line 0.
+    fn row_y(&self, line: usize) -> i32 {
+        // The line under the header is between it and the first row
+        let separator = if line > 0 { 1 } else { 0 };
+        self.y1 + line as i32 * self.row_height + separator
+    }
+}
+
+impl<'a, T, S> util::Parts<'a, T> for Cells<'a, T, S>
+where
+    T: theme::Theme<'a>,
+    S: Source + ?Sized,
+{
+    type Capacity = CellCapacity;
+
+    /// Moves on to the next part of the table: the stripe of a row, if any, and then its cells.
+    fn advance(&mut self) -> Option<util::Part<'a, T, CellCapacity>> {
+        loop {
+            if self.line > self.rows.len() {
+                return None;
+            }
+            let step = self.step;
+            if step > self.bounds.len() {
+                self.line += 1;
+                self.step = 0;
+                continue;
+            }
+            self.step += 1;
+
+            let grid = self.grid;
+            let y = grid.row_y(self.line);
+            let row = self.line.checked_sub(1).map(|i| self.rows.start + i);
+
+            if step == 0 {
+                match row {
+                    Some(row) if row % 2 == 1 => {
+                        return Some(util::Part::Rect(
+                            egrectangle!(
+                                (grid.x1, y),
+                                (grid.x2, y + grid.row_height - 1),
+                                fill = Some(self.stripe_color)
+                            )
+                            .into_iter(),
+                        ));
+                    }
+                    _ => continue,
+                }
+            }
+
+            let index = step - 1;
+            let column = &self.columns[index];
+            let (x, width) = self.bounds[index];
+            let mut text = Cell::new();
+            match row {
+                Some(row) => self.source.cell(row, index, &mut text),
+                None => util::push_truncated(&mut text, column.title),
+            }
+
+            let fits = ((width - 2 * grid.padding) / grid.advance.max(1)).max(0) as usize;
+            let len = text.len().min(fits);
+            let slack = width - 2 * grid.padding - len as i32 * grid.advance;
+            let x = match column.align {
+                Align::Start => x + grid.padding,
+                Align::Center => x + grid.padding + slack / 2,
+                Align::End => x + grid.padding + slack,
+            };
+            let position = embedded_graphics::geometry::Point::new(x, y + grid.padding);
+            return Some(util::Part::Glyphs(
+                util::Glyphs::new(text, position, grid.advance, self.text_color).range(0..len),
+            ));
+        }
+    }
+}
+
+/// The height of a row of a table, including the header row.
+fn row_height<'a, T>(theme: &T, glyph_height: u32) -> i32
+where
+    T: theme::Theme<'a>,
+{
+    glyph_height as i32 + 2 * theme.spacing() as i32
+}
+
+/// The number of rows that fit below the header of a table with size `size`.
+fn visible_rows(size: embedded_graphics::geometry::Size, row_height: i32) -> usize {
+    // The border, the header and the line under it
+    let height = size.height as i32 - 3 - row_height;
+    if row_height > 0 {
+        (height / row_height).max(0) as usize
+    } else {
+        0
+    }
+}
@@ -0,0 +1,425 @@
+use crate::children;
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::marker;
+use embedded_graphics::egrectangle;
+
+/// The state of a tab view.
+///
+/// The states of the pages are kept by the application, so that pages which are not shown keep
+/// theirs, including their layout.
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    selected: usize,
+    editing: bool,
+    pressed: Option<usize>,
+}
+
+/// A container that shows one of its pages at a time, below a strip with a tab for every page.
+///
+/// Only the page of the selected tab is laid out, drawn and receives events, so switching between
+/// pages is cheap however many there are.  Tapping a tab selects it, and swiping left or right over
+/// the page selects the next or previous one.  While the tab view has the focus, the left and right
+/// arrows select the tab next to the current one, without wrapping around.  `Next` and `Previous`
+/// only do so in editing mode, which `Activate` toggles; see `event::Navigation`.
+pub struct Tabs<'a, T, M, X> {
+    state: &'a mut State,
+    titles: &'a [&'a str],
+    pages: X,
+    on_change: Option<fn(usize) -> M>,
+    phantom: marker::PhantomData<T>,
+}
+
+/// The pixels of the tab strip.
+pub struct Strip<'a, T>
+where
+    T: theme::Theme<'a>,
+{
+    titles: &'a [&'a str],
+    selected: usize,
+    position: embedded_graphics::geometry::Point,
+    width: i32,
+    height: i32,
+    fill_color: T::Color,
+    text_color: T::Color,
+    selected_text_color: T::Color,
+    index: usize,
+    current: Option<TabIter<'a, T>>,
+}
+
+type TabIter<'a, T> = core::iter::Chain<
+    util::OptionalIter<util::RectIter<<T as theme::Theme<'a>>::Color>>,
+    util::FontIter<'a, T>,
+>;
+
+/// Forwards only the selected page to the child visitor of a tab view.
+struct Selected<'v, V> {
+    visitor: &'v mut V,
+    selected: usize,
+    index: usize,
+}
+
+impl State {
+    /// The index of the selected tab.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects the tab at `index`, which is limited to the number of tabs when the tab view is next
+    /// created.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index;
+    }
+}
+
+impl<'a, T, M, X> Tabs<'a, T, M, X>
+where
+    T: theme::Theme<'a>,
+    X: children::Children<'a, T, M>,
+{
+    /// Creates a tab view with a tab for each of `titles`, showing the page of the selected tab
+    /// from `pages`, e.g. a tuple of containers.
+    pub fn new(state: &'a mut State, titles: &'a [&'a str], pages: X) -> Self {
+        let count = titles.len().min(pages.len());
+        let titles = &titles[..count];
+        state.selected = state.selected.min(count.saturating_sub(1));
+        let on_change = None;
+        let phantom = marker::PhantomData;
+        Self {
+            state,
+            titles,
+            pages,
+            on_change,
+            phantom,
+        }
+    }
+
+    /// Sets the function that creates the message emitted with the index of a tab when it is
+    /// selected.
+    pub fn on_change(mut self, on_change: fn(usize) -> M) -> Self {
+        self.on_change = Some(on_change);
+        self
+    }
+
+    /// Selects the tab next to the selected one, and returns whether there was one.
+    fn step(&mut self, context: &mut widget::EventContext<T, M>, next: bool) -> bool {
+        let selected = self.state.selected;
+        let index = if next {
+            Some(selected + 1).filter(|&index| index < self.titles.len())
+        } else {
+            selected.checked_sub(1)
+        };
+        match index {
+            Some(index) => {
+                self.select(context, index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn select(&mut self, context: &mut widget::EventContext<T, M>, index: usize) {
+        if index != self.state.selected {
+            self.state.selected = index;
+            if let Some(on_change) = self.on_change {
+                context.emit(on_change(index));
+            }
+        }
+    }
+
+    /// The tab under `point`, if any.
+    fn tab_at(
+        &self,
+        context: &widget::EventContext<T, M>,
+        point: embedded_graphics::geometry::Point,
+    ) -> Option<usize> {
+        let x = point.x - context.position.x;
+        let y = point.y - context.position.y;
+        let width = context.size.width as i32;
+        if self.titles.is_empty()
+            || x < 0
+            || x >= width
+            || y < 0
+            || y >= strip_height(context.theme)
+        {
+            return None;
+        }
+        Some((x * self.titles.len() as i32 / width) as usize)
+    }
+}
+
+impl<'a, T, M, X> widget::Node<'a, T, M> for Tabs<'a, T, M, X>
+where
+    T: theme::Theme<'a>,
+    X: children::Children<'a, T, M>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        let count = self.titles.len().min(1);
+        let mut visitor = visitor.accept_node(self, count);
+        if count > 0 {
+            self.pages.visit_children(Selected {
+                visitor: &mut visitor,
+                selected: self.state.selected,
+                index: 0,
+            });
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let count = self.titles.len().min(1);
+        let mut visitor = visitor.accept_node_mut(self, count);
+        if count > 0 {
+            self.pages.visit_children_mut(Selected {
+                visitor: &mut visitor,
+                selected: self.state.selected,
+                index: 0,
+            });
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, T, M, X> widget::Widget<'a, T, M> for Tabs<'a, T, M, X>
+where
+    T: theme::Theme<'a>,
+    X: children::Children<'a, T, M>,
+{
+    type Output = core::iter::Chain<Strip<'a, T>, util::RectIter<T::Color>>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let theme = context.theme;
+        let height = strip_height(theme);
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+
+        let fill_color = if self.state.editing && context.focused {
+            theme.focus_color()
+        } else {
+            theme.fill_color()
+        };
+        let strip = Strip {
+            titles: self.titles,
+            selected: self.state.selected,
+            position: context.position,
+            width: context.size.width as i32,
+            height,
+            fill_color,
+            text_color: theme.text_color(),
+            selected_text_color: theme.background_color(),
+            index: 0,
+            current: None,
+        };
+
+        let line = egrectangle!(
+            (x1, y1 + height),
+            (x2, y1 + height),
+            stroke = Some(theme.border_color())
+        );
+
+        strip.chain(line.into_iter())
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        layout::Style {
+            flex_grow: 1.0,
+            flex_shrink: 1.0,
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_self: stretch::style::AlignSelf::Stretch,
+            align_items: stretch::style::AlignItems::Stretch,
+            padding: stretch::geometry::Rect {
+                start: stretch::style::Dimension::Points(0.0),
+                end: stretch::style::Dimension::Points(0.0),
+                top: stretch::style::Dimension::Points(strip_height(theme) as f32 + 1.0),
+                bottom: stretch::style::Dimension::Points(0.0),
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.titles.len() > 1
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        match (context.phase, event) {
+            (event::Phase::Capture, _) => false,
+            (_, event::Event::Pointer(pointer)) => {
+                let tab = self.tab_at(context, pointer.position);
+                match pointer.action {
+                    event::PointerAction::Down => {
+                        self.state.pressed = tab;
+                        tab.is_some()
+                    }
+                    event::PointerAction::Move => self.state.pressed.is_some(),
+                    event::PointerAction::Up => {
+                        let pressed = self.state.pressed.take();
+                        if let Some(index) = pressed.filter(|&index| Some(index) == tab) {
+                            self.select(context, index);
+                        }
+                        pressed.is_some()
+                    }
+                }
+            }
+            // Swiping the page to the left brings in the page on its right
+            (_, event::Event::Gesture(event::Gesture::Swipe { direction, .. })) => {
+                match direction {
+                    event::Direction::Left => self.step(context, true),
+                    event::Direction::Right => self.step(context, false),
+                    _ => false,
+                }
+            }
+            (event::Phase::Target, event::Event::Navigation(navigation)) => match *navigation {
+                event::Navigation::Activate => {
+                    self.state.editing = !self.state.editing;
+                    true
+                }
+                event::Navigation::Next if self.state.editing => {
+                    self.step(context, true);
+                    true
+                }
+                event::Navigation::Previous if self.state.editing => {
+                    self.step(context, false);
+                    true
+                }
+                // Beyond the first and last tab, the arrows move the focus out of the tab view
+                event::Navigation::Right => self.step(context, true),
+                event::Navigation::Left => self.step(context, false),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Strip<'a, T>
+where
+    T: theme::Theme<'a>,
+{
+    type Item = embedded_graphics::drawable::Pixel<T::Color>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+        use embedded_graphics::style::WithStyle;
+        use embedded_graphics::transform::Transform;
+
+        loop {
+            if let Some(pixel) = self.current.as_mut().and_then(Iterator::next) {
+                return Some(pixel);
+            }
+
+            let index = self.index;
+            let title: &'a str = *self.titles.get(index)?;
+            self.index += 1;
+
+            let count = self.titles.len() as i32;
+            let x1 = self.position.x + self.width * index as i32 / count;
+            let x2 = self.position.x + self.width * (index as i32 + 1) / count - 1;
+            let y1 = self.position.y;
+            let y2 = y1 + self.height - 1;
+
+            // The selected tab is filled, so that it stands out also on monochrome displays
+            let selected = index == self.selected;
+            let fill = if selected {
+                Some(egrectangle!((x1, y1), (x2, y2), fill = Some(self.fill_color)).into_iter())
+            } else {
+                None
+            };
+            let text_color = if selected {
+                self.selected_text_color
+            } else {
+                self.text_color
+            };
+
+            let text = T::Font::render_str(title);
+            let size = text.size();
+            let x = x1 + (x2 - x1 + 1 - size.width as i32) / 2;
+            let y = y1 + (self.height - size.height as i32) / 2;
+            let text = text
+                .translate(embedded_graphics::geometry::Point::new(x, y))
+                .stroke(Some(text_color))
+                .into_iter();
+
+            self.current = Some(fill.into_iter().flatten().chain(text));
+        }
+    }
+}
+
+impl<'v, 'a, T, M, V> widget::ChildVisitor<'a, T, M> for Selected<'v, V>
+where
+    T: theme::Theme<'a>,
+    V: widget::ChildVisitor<'a, T, M>,
+{
+    type Output = ();
+
+    fn accept_child<W>(&mut self, widget: &W)
+    where
+        W: widget::Node<'a, T, M>,
+    {
+        if self.index == self.selected {
+            self.visitor.accept_child(widget);
+        }
+        self.index += 1;
+    }
+
+    fn end(self) -> Self::Output {}
+}
+
+impl<'v, 'a, T, M, V> widget::MutChildVisitor<'a, T, M> for Selected<'v, V>
+where
+    T: theme::Theme<'a>,
+    V: widget::MutChildVisitor<'a, T, M>,
+{
+    type Output = ();
+
+    fn accept_child_mut<W>(&mut self, widget: &mut W)
+    where
+        W: widget::Node<'a, T, M>,
+    {
+        if self.index == self.selected {
+            self.visitor.accept_child_mut(widget);
+        }
+        self.index += 1;
+    }
+
+    fn end(self) -> Self::Output {}
+}
+
+/// The height of the tab strip, without the line under it.
+fn strip_height<'a, T>(theme: &T) -> i32
+where
+    T: theme::Theme<'a>,
+{
+    use embedded_graphics::fonts::Font;
+    use embedded_graphics::geometry::Dimensions;
+
+    T::Font::render_str("0").size().height as i32 + 2 * theme.spacing() as i32
+}
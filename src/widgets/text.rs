@@ -20,26 +20,26 @@ impl<'a> Text<'a> {
     }
 }
 
-impl<'a, T> widget::Node<'a, T> for Text<'a>
+impl<'a, T, M> widget::Node<'a, T, M> for Text<'a>
 where
     T: theme::Theme<'a>,
 {
     fn visit<V>(&self, visitor: V) -> V::Output
     where
-        V: widget::Visitor<'a, T>,
+        V: widget::Visitor<'a, T, M>,
     {
         visitor.accept_leaf(self)
     }
 
     fn visit_mut<V>(&mut self, visitor: V) -> V::Output
     where
-        V: widget::MutVisitor<'a, T>,
+        V: widget::MutVisitor<'a, T, M>,
     {
         visitor.accept_leaf_mut(self)
     }
 }
 
-impl<'a, T> widget::Widget<'a, T> for Text<'a>
+impl<'a, T, M> widget::Widget<'a, T, M> for Text<'a>
 where
     T: theme::Theme<'a>,
 {
@@ -0,0 +1,580 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::ops;
+use embedded_graphics::egline;
+use embedded_graphics::egrectangle;
+
+/// The capacity of the buffer that holds the text.
+pub type Capacity = heapless::consts::U32;
+
+/// The minimum number of characters that a text input shows.
+const MIN_CHARS: f32 = 4.0;
+
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    text: heapless::String<Capacity>,
+    cursor: usize,
+    anchor: Option<usize>,
+    scroll: usize,
+    pressed: bool,
+}
+
+/// A single line of editable text.
+///
+/// Unlike the values of most other widgets, the text is owned by the widget's `State`, since it
+/// changes with every key press; the application is notified of changes by the message emitted
+/// with the new text.  Only printable ASCII characters are accepted.
+///
+/// The text scrolls horizontally to keep the cursor visible when it is wider than the widget.  It
+/// can be selected by dragging the pointer over it, or by moving the cursor with `Edit::Move`
+/// events that extend the selection.  `Activate` submits the text.
+#[derive(Debug)]
+pub struct TextInput<'a, M> {
+    state: &'a mut State,
+    max_length: usize,
+    password: bool,
+    on_change: Option<fn(&str) -> M>,
+    on_submit: Option<fn(&str) -> M>,
+}
+
+impl State {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, truncating it to the capacity, and moves the cursor to its end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        for c in text.chars().filter(|c| is_printable(*c)) {
+            if self.text.push(c).is_err() {
+                break;
+            }
+        }
+        self.cursor = self.text.len();
+        self.anchor = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    /// The range of selected characters, if any.
+    fn selection(&self) -> Option<ops::Range<usize>> {
+        match self.anchor {
+            Some(anchor) if anchor < self.cursor => Some(anchor..self.cursor),
+            Some(anchor) if anchor > self.cursor => Some(self.cursor..anchor),
+            _ => None,
+        }
+    }
+
+    /// Replaces the characters in `range` with `c`, if any, and moves the cursor after them.
+    fn replace(&mut self, range: ops::Range<usize>, c: Option<char>) {
+        let mut text = heapless::String::new();
+        // The text only ever shrinks or grows within its capacity, so pushing can't fail
+        let _ = text.push_str(&self.text[..range.start]);
+        if let Some(c) = c {
+            let _ = text.push(c);
+        }
+        let _ = text.push_str(&self.text[range.end..]);
+        self.text = text;
+        self.cursor = range.start + c.map_or(0, |_| 1);
+        self.anchor = None;
+    }
+}
+
+impl<'a, M> TextInput<'a, M> {
+    pub fn new(state: &'a mut State) -> Self {
+        let max_length = state.text.capacity();
+        let password = false;
+        let on_change = None;
+        let on_submit = None;
+        // The text may have been changed since the cursor was last moved
+        state.cursor = state.cursor.min(state.text.len());
+        state.anchor = state.anchor.map(|anchor| anchor.min(state.text.len()));
+        Self {
+            state,
+            max_length,
+            password,
+            on_change,
+            on_submit,
+        }
+    }
+
+    /// Limits the number of characters that can be entered; at most the capacity.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length.min(self.state.text.capacity());
+        self
+    }
+
+    /// Displays every character as `*`, e.g. for passwords.
+    pub fn password(mut self, password: bool) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// Sets the function that creates the message emitted with the new text when it is changed.
+    pub fn on_change(mut self, on_change: fn(&str) -> M) -> Self {
+        self.on_change = Some(on_change);
+        self
+    }
+
+    /// Sets the function that creates the message emitted with the text when it is submitted.
+    pub fn on_submit(mut self, on_submit: fn(&str) -> M) -> Self {
+        self.on_submit = Some(on_submit);
+        self
+    }
+
+    fn changed<T>(&self, context: &mut widget::EventContext<T, M>) {
+        if let Some(on_change) = self.on_change {
+            context.emit(on_change(&self.state.text));
+        }
+    }
+
+    fn insert<T>(&mut self, context: &mut widget::EventContext<T, M>, c: char) {
+        if !is_printable(c) {
+            return;
+        }
+        let cursor = self.state.cursor;
+        let range = self.state.selection().unwrap_or(cursor..cursor);
+        if self.state.text.len() - range.len() < self.max_length {
+            self.state.replace(range, Some(c));
+            self.changed(context);
+        }
+    }
+
+    /// Deletes the selection, or the character before or after the cursor.
+    fn delete<T>(&mut self, context: &mut widget::EventContext<T, M>, forward: bool) {
+        let cursor = self.state.cursor;
+        let range = match self.state.selection() {
+            Some(selection) => selection,
+            None if forward && cursor < self.state.text.len() => cursor..cursor + 1,
+            None if !forward && cursor > 0 => cursor - 1..cursor,
+            None => return,
+        };
+        self.state.replace(range, None);
+        self.changed(context);
+    }
+
+    /// Moves the cursor, and returns whether it could be moved.
+    fn motion(&mut self, motion: event::Motion, select: bool) -> bool {
+        let state = &mut *self.state;
+        let selection = state.selection();
+        let cursor = match (motion, selection) {
+            // Without extending it, moving collapses the selection to the side of the motion
+            (event::Motion::Left, Some(selection)) if !select => selection.start,
+            (event::Motion::Right, Some(selection)) if !select => selection.end,
+            (event::Motion::Left, _) if state.cursor > 0 => state.cursor - 1,
+            (event::Motion::Right, _) if state.cursor < state.text.len() => state.cursor + 1,
+            (event::Motion::Left, _) | (event::Motion::Right, _) => return false,
+            (event::Motion::Home, _) => 0,
+            (event::Motion::End, _) => state.text.len(),
+        };
+
+        state.anchor = if select {
+            state.anchor.or(Some(state.cursor))
+        } else {
+            None
+        };
+        state.cursor = cursor;
+        true
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for TextInput<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for TextInput<'a, M>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<
+        core::iter::Chain<
+            core::iter::Chain<
+                util::RectIter<T::Color>,
+                util::OptionalIter<util::RectIter<T::Color>>,
+            >,
+            util::Glyphs<'a, T, Capacity>,
+        >,
+        util::OptionalIter<util::LineIter<T::Color>>,
+    >;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let theme = context.theme;
+        let glyph_size = T::Font::render_str("0").size();
+        let advance = glyph_size.width as i32;
+        let height = glyph_size.height as i32;
+        let padding = theme.spacing() as i32;
+
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+        let xt = x1 + padding;
+        let yt = y1 + (context.size.height as i32 - height) / 2;
+
+        let state = &*self.state;
+        let visible = visible_chars(context.size, advance, padding);
+        let scroll = scroll_offset(state.cursor, state.scroll, visible, state.text.len());
+        let column = |index: usize| xt + (index as i32 - scroll as i32) * advance;
+
+        let fill = if context.hovered {
+            Some(theme.hover_color())
+        } else {
+            None
+        };
+        let border = egrectangle!(
+            (x1, y1),
+            (x2, y2),
+            fill = fill,
+            stroke = Some(theme.border_color())
+        );
+
+        // Only the visible part of the selection is highlighted
+        let selection = state.selection().and_then(|selection| {
+            let start = selection.start.max(scroll);
+            let end = selection.end.min(scroll + visible);
+            if start < end {
+                Some(start..end)
+            } else {
+                None
+            }
+        });
+        let highlight = selection.clone().map(|selection| {
+            egrectangle!(
+                (column(selection.start), yt),
+                (column(selection.end) - 1, yt + height - 1),
+                fill = Some(theme.fill_color())
+            )
+            .into_iter()
+        });
+
+        let position = embedded_graphics::geometry::Point::new(xt, yt);
+        let mut glyphs =
+            util::Glyphs::new(state.text.clone(), position, advance, theme.text_color())
+                .range(scroll..scroll + visible);
+        if let Some(selection) = selection {
+            glyphs = glyphs.highlight(selection, theme.background_color());
+        }
+        if self.password {
+            glyphs = glyphs.mask(b'*');
+        }
+
+        let caret = if context.focused {
+            let x = column(state.cursor);
+            Some(
+                egline!(
+                    (x, yt),
+                    (x, yt + height - 1),
+                    stroke = Some(theme.text_color())
+                )
+                .into_iter(),
+            )
+        } else {
+            None
+        };
+
+        border
+            .into_iter()
+            .chain(highlight.into_iter().flatten())
+            .chain(glyphs)
+            .chain(caret.into_iter().flatten())
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let glyph_size = T::Font::render_str("0").size();
+        let padding = 2.0 * theme.spacing();
+        let height = stretch::style::Dimension::Points(glyph_size.height as f32 + padding);
+        let min_width = MIN_CHARS * glyph_size.width as f32 + padding;
+
+        layout::Style {
+            flex_shrink: 1.0,
+            flex_grow: 1.0,
+            flex_basis: stretch::style::Dimension::Auto,
+            align_self: stretch::style::AlignSelf::Stretch,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Auto,
+                height,
+            },
+            min_size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(min_width),
+                height,
+            },
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &self.state.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut self.state.layout
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let advance = T::Font::render_str("0").size().width as i32;
+        let padding = context.theme.spacing() as i32;
+        let visible = visible_chars(context.size, advance, padding);
+        let len = self.state.text.len();
+        self.state.scroll = scroll_offset(self.state.cursor, self.state.scroll, visible, len);
+
+        // The character boundary closest to a point
+        let scroll = self.state.scroll;
+        let origin = context.position.x + padding;
+        let boundary = |point: embedded_graphics::geometry::Point| {
+            let x = point.x - origin + advance / 2;
+            let index = scroll as i32 + x.div_euclid(advance.max(1));
+            (index.max(0) as usize).min(len)
+        };
+
+        let handled = match event {
+            event::Event::Pointer(pointer) => match pointer.action {
+                event::PointerAction::Down => {
+                    let index = boundary(pointer.position);
+                    self.state.pressed = true;
+                    self.state.cursor = index;
+                    self.state.anchor = Some(index);
+                    true
+                }
+                event::PointerAction::Move if self.state.pressed => {
+                    self.state.cursor = boundary(pointer.position);
+                    true
+                }
+                event::PointerAction::Move => false,
+                event::PointerAction::Up => {
+                    if self.state.pressed {
+                        self.state.cursor = boundary(pointer.position);
+                    }
+                    if self.state.selection().is_none() {
+                        self.state.anchor = None;
+                    }
+                    self.state.pressed = false;
+                    true
+                }
+            },
+            event::Event::Edit(edit) => {
+                match *edit {
+                    event::Edit::Insert(c) => self.insert(context, c),
+                    event::Edit::Backspace => self.delete(context, false),
+                    event::Edit::Delete => self.delete(context, true),
+                    event::Edit::Move { motion, select } => {
+                        self.motion(motion, select);
+                    }
+                    event::Edit::SelectAll => {
+                        self.state.anchor = Some(0);
+                        self.state.cursor = self.state.text.len();
+                    }
+                }
+                true
+            }
+            event::Event::Navigation(navigation) => match *navigation {
+                // From the start or end of the text, the arrows move the focus instead
+                event::Navigation::Left => self.motion(event::Motion::Left, false),
+                event::Navigation::Right => self.motion(event::Motion::Right, false),
+                event::Navigation::Activate => {
+                    if let Some(on_submit) = self.on_submit {
+                        context.emit(on_submit(&self.state.text));
+                    }
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+        let len = self.state.text.len();
+        self.state.scroll = scroll_offset(self.state.cursor, self.state.scroll, visible, len);
+        handled
+    }
+}
+
+fn is_printable(c: char) -> bool {
+    c.is_ascii() && !c.is_ascii_control()
+}
+
+/// The number of characters that fit into a text input of size `size`.
+fn visible_chars(size: embedded_graphics::geometry::Size, advance: i32, padding: i32) -> usize {
+    let width = size.width as i32 - 2 * padding;
+    (width / advance.max(1)).max(1) as usize
+}
+
+/// The index of the first visible character, which is changed as little as possible from `scroll`
+/// to keep the cursor visible and to show as much of the text as fits.
+fn scroll_offset(cursor: usize, scroll: usize, visible: usize, len: usize) -> usize {
+    // Leave room for the cursor after the last character
+    let scroll = scroll.min((len + 1).saturating_sub(visible));
+    if cursor < scroll {
+        cursor
+    } else if cursor >= scroll + visible {
+        cursor + 1 - visible
+    } else {
+        scroll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message;
+    use crate::testing;
+
+    /// Lets `input` handle `event` while it is large enough to show four characters.
+    fn handle(input: &mut TextInput<()>, event: event::Event) -> bool {
+        let theme = testing::theme();
+        let layout = layout::Layout::new();
+        let mut messages = message::Queue::new();
+        let mut events = event::Queue::new();
+        let mut context = widget::EventContext {
+            theme: &theme,
+            position: embedded_graphics::geometry::Point::new(0, 0),
+            size: embedded_graphics::geometry::Size::new(28, 12),
+            phase: event::Phase::Target,
+            now: core::time::Duration::default(),
+            messages: &mut messages,
+            events: &mut events,
+            layout: &layout,
+        };
+        input.handle_event(&mut context, &event)
+    }
+
+    fn edit(state: &mut State, edit: event::Edit) {
+        handle(&mut TextInput::new(state), edit.into());
+    }
+
+    fn move_to(state: &mut State, motion: event::Motion) {
+        let select = false;
+        edit(state, event::Edit::Move { motion, select });
+    }
+
+    #[test]
+    fn insert_and_delete_at_the_ends() {
+        let mut state = State::default();
+        state.set_text("bc");
+
+        move_to(&mut state, event::Motion::Home);
+        edit(&mut state, event::Edit::Insert('a'));
+        assert_eq!((state.text(), state.cursor), ("abc", 1));
+        move_to(&mut state, event::Motion::End);
+        edit(&mut state, event::Edit::Insert('d'));
+        assert_eq!((state.text(), state.cursor), ("abcd", 4));
+
+        edit(&mut state, event::Edit::Delete);
+        assert_eq!((state.text(), state.cursor), ("abcd", 4));
+        edit(&mut state, event::Edit::Backspace);
+        assert_eq!((state.text(), state.cursor), ("abc", 3));
+
+        move_to(&mut state, event::Motion::Home);
+        edit(&mut state, event::Edit::Backspace);
+        assert_eq!((state.text(), state.cursor), ("abc", 0));
+        edit(&mut state, event::Edit::Delete);
+        assert_eq!((state.text(), state.cursor), ("bc", 0));
+    }
+
+    #[test]
+    fn insert_at_capacity() {
+        let mut state = State::default();
+        state.set_text("abc");
+        let mut input = TextInput::new(&mut state).max_length(3);
+        handle(&mut input, event::Edit::Insert('d').into());
+        assert_eq!(input.state.text(), "abc");
+        // The selection is replaced, so there is room for the new character
+        handle(&mut input, event::Edit::SelectAll.into());
+        handle(&mut input, event::Edit::Insert('d').into());
+        assert_eq!(input.state.text(), "d");
+
+        let long = "0123456789012345678901234567890123456789";
+        state.set_text(long);
+        assert_eq!(state.text(), &long[..state.text.capacity()]);
+        edit(&mut state, event::Edit::Insert('x'));
+        assert_eq!(state.text(), &long[..state.text.capacity()]);
+    }
+
+    #[test]
+    fn cursor_stays_on_character_boundaries() {
+        let mut state = State::default();
+        // Only single byte characters are accepted, so the cursor can't end up within one
+        state.set_text("aéb");
+        assert_eq!((state.text(), state.cursor), ("ab", 2));
+        edit(&mut state, event::Edit::Insert('ü'));
+        assert_eq!((state.text(), state.cursor), ("ab", 2));
+
+        move_to(&mut state, event::Motion::Left);
+        move_to(&mut state, event::Motion::Left);
+        assert_eq!(state.cursor, 0);
+        let handled = handle(
+            &mut TextInput::new(&mut state),
+            event::Navigation::Left.into(),
+        );
+        assert!(!handled);
+        move_to(&mut state, event::Motion::Right);
+        move_to(&mut state, event::Motion::Right);
+        move_to(&mut state, event::Motion::Right);
+        assert_eq!(state.cursor, 2);
+    }
+
+    #[test]
+    fn scrolls_to_the_cursor() {
+        let mut state = State::default();
+        state.set_text("abcdefghij");
+
+        // There is room for the cursor after the last character
+        move_to(&mut state, event::Motion::End);
+        assert_eq!((state.cursor, state.scroll), (10, 7));
+        move_to(&mut state, event::Motion::Home);
+        assert_eq!((state.cursor, state.scroll), (0, 0));
+        for _ in 0..4 {
+            move_to(&mut state, event::Motion::Right);
+        }
+        assert_eq!((state.cursor, state.scroll), (4, 1));
+        move_to(&mut state, event::Motion::Left);
+        assert_eq!((state.cursor, state.scroll), (3, 1));
+        move_to(&mut state, event::Motion::Left);
+        move_to(&mut state, event::Motion::Left);
+        assert_eq!((state.cursor, state.scroll), (1, 1));
+        move_to(&mut state, event::Motion::Left);
+        assert_eq!((state.cursor, state.scroll), (0, 0));
+    }
+
+    #[test]
+    fn scrolls_back_when_the_text_shrinks() {
+        assert_eq!(scroll_offset(3, 7, 4, 4), 1);
+        assert_eq!(scroll_offset(0, 7, 4, 0), 0);
+    }
+}
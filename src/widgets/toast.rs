@@ -0,0 +1,380 @@
+use crate::event;
+use crate::layout;
+use crate::theme;
+use crate::util;
+use crate::widget;
+use core::time;
+use embedded_graphics::egrectangle;
+
+/// The maximum number of toasts shown at once.
+pub type MaxToasts = heapless::consts::U4;
+/// The maximum length of the text of a toast, in bytes.
+pub type Capacity = heapless::consts::U32;
+/// The text of a toast.
+pub type Text = heapless::String<Capacity>;
+
+/// Above dialogs, so that notifications are seen whatever else is open.
+const TOAST_LAYER: u8 = 3;
+
+/// The toasts that are currently shown.
+#[derive(Debug, Default)]
+pub struct State {
+    layout: layout::State,
+    toasts: heapless::Vec<Toast, MaxToasts>,
+    /// The layout states of the toasts, which are reused by later toasts so that the layout doesn't
+    /// grow with every toast shown.
+    slots: heapless::Vec<layout::State, MaxToasts>,
+}
+
+#[derive(Debug)]
+struct Toast {
+    text: Text,
+    duration: time::Duration,
+    /// When the toast disappears, which is only known once it has been drawn for the first time.
+    expires: Option<time::Duration>,
+    /// The index of the layout state of the toast, which it keeps while it is shown.
+    slot: usize,
+    pressed: bool,
+    /// Whether the toast has expired or been tapped, so that it is hidden until it is removed.
+    dismissed: bool,
+}
+
+/// Shows short notifications over other content, which disappear on their own.
+///
+/// Toasts wrap the content that they are shown over, and are meant to be the root of the widget
+/// tree.  They are stacked at the bottom of the screen with the most recent one lowest, and do not
+/// take the focus or any input, except that tapping a toast dismisses it early; taps next to or
+/// between the toasts go to the content.  A toast is shown for the duration it was given from the
+/// first frame it appears in, going by the time passed to `Emui::update`.
+#[derive(Debug)]
+pub struct Toasts<'a, N> {
+    layout: &'a mut layout::State,
+    content: N,
+    items: heapless::Vec<Item<'a>, MaxToasts>,
+}
+
+/// A single toast, drawn above everything else.
+#[derive(Debug)]
+struct Item<'a> {
+    layout: &'a mut layout::State,
+    toast: &'a mut Toast,
+    /// The position of the toast in the stack, counted from the top.
+    index: usize,
+    count: usize,
+}
+
+/// The dimensions of a toast, in pixels.
+#[derive(Clone, Copy, Debug)]
+struct Metrics {
+    padding: i32,
+    advance: i32,
+    height: i32,
+}
+
+impl State {
+    /// Shows `text` for `duration`, truncated to `Capacity`.
+    ///
+    /// If `MaxToasts` toasts are already shown, the oldest one is dismissed to make room.
+    pub fn show(&mut self, text: &str, duration: time::Duration) {
+        remove_dismissed(&mut self.toasts);
+        if self.toasts.len() == self.toasts.capacity() {
+            remove(&mut self.toasts, 0);
+        }
+
+        let _ = self.slots.resize_default(self.slots.capacity());
+        let toasts = &self.toasts;
+        let slot = (0..self.slots.len())
+            .find(|&slot| toasts.iter().all(|toast| toast.slot != slot))
+            .unwrap_or_default();
+        let mut toast = Toast {
+            text: Text::new(),
+            duration,
+            expires: None,
+            slot,
+            pressed: false,
+            dismissed: false,
+        };
+        util::push_truncated(&mut toast.text, text);
+        let _ = self.toasts.push(toast);
+    }
+
+    /// Dismisses all toasts.
+    pub fn clear(&mut self) {
+        self.toasts.clear();
+    }
+
+    /// The number of toasts that are shown.
+    pub fn len(&self) -> usize {
+        self.toasts.iter().filter(|toast| !toast.dismissed).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, N> Toasts<'a, N> {
+    /// Creates a widget that shows the toasts in `state` over `content`.
+    pub fn new(state: &'a mut State, content: N) -> Self {
+        let State {
+            layout,
+            toasts,
+            slots,
+        } = state;
+        remove_dismissed(toasts);
+
+        // Every toast has a slot of its own, so each of them is taken at most once
+        let count = toasts.len();
+        let mut slots: heapless::Vec<Option<&'a mut layout::State>, MaxToasts> =
+            slots.iter_mut().map(Some).collect();
+        let items = toasts
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, toast)| {
+                let layout = slots.get_mut(toast.slot)?.take()?;
+                Some(Item {
+                    layout,
+                    toast,
+                    index,
+                    count,
+                })
+            })
+            .collect();
+
+        Self {
+            layout,
+            content,
+            items,
+        }
+    }
+}
+
+impl<'a, N, T, M> widget::Node<'a, T, M> for Toasts<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        use widget::ChildVisitor;
+
+        // Dismissed toasts are neither drawn nor hit until they are removed
+        let shown = self.items.iter().filter(|item| !item.toast.dismissed);
+        let mut visitor = visitor.accept_node(self, 1 + shown.clone().count());
+        visitor.accept_child(&self.content);
+        for item in shown {
+            visitor.accept_child(item);
+        }
+        visitor.end()
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        use widget::MutChildVisitor;
+
+        let count = self
+            .items
+            .iter()
+            .filter(|item| !item.toast.dismissed)
+            .count();
+        let mut visitor = visitor.accept_node_mut(self, 1 + count);
+        visitor.accept_child_mut(&mut self.content);
+        for item in self.items.iter_mut() {
+            if !item.toast.dismissed {
+                visitor.accept_child_mut(item);
+            }
+        }
+        visitor.end()
+    }
+}
+
+impl<'a, N, T, M> widget::Widget<'a, T, M> for Toasts<'a, N>
+where
+    N: widget::Node<'a, T, M>,
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Empty<embedded_graphics::drawable::Pixel<T::Color>>;
+
+    fn draw(&self, _context: &widget::DrawContext<T>) -> Self::Output {
+        core::iter::empty()
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        layout::Style {
+            flex_grow: 1.0,
+            flex_direction: stretch::style::FlexDirection::Column,
+            align_items: stretch::style::AlignItems::Stretch,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Percent(1.0),
+                height: stretch::style::Dimension::Percent(1.0),
+            },
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+}
+
+impl<'a, T, M> widget::Node<'a, T, M> for Item<'a>
+where
+    T: theme::Theme<'a>,
+{
+    fn visit<V>(&self, visitor: V) -> V::Output
+    where
+        V: widget::Visitor<'a, T, M>,
+    {
+        visitor.accept_leaf(self)
+    }
+
+    fn visit_mut<V>(&mut self, visitor: V) -> V::Output
+    where
+        V: widget::MutVisitor<'a, T, M>,
+    {
+        visitor.accept_leaf_mut(self)
+    }
+}
+
+impl<'a, T, M> widget::Widget<'a, T, M> for Item<'a>
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<util::RectIter<T::Color>, util::Glyphs<'a, T, Capacity>>;
+
+    fn draw(&self, context: &widget::DrawContext<T>) -> Self::Output {
+        let theme = context.theme;
+        let metrics = Metrics::new(theme);
+        let x1 = context.position.x;
+        let y1 = context.position.y;
+        let x2 = x1 + context.size.width as i32 - 1;
+        let y2 = y1 + context.size.height as i32 - 1;
+
+        let background = egrectangle!(
+            (x1, y1),
+            (x2, y2),
+            fill = Some(theme.fill_color()),
+            stroke = Some(theme.border_color())
+        );
+        let text = self.toast.text.clone();
+        let position =
+            embedded_graphics::geometry::Point::new(x1 + 1 + metrics.padding, y1 + metrics.padding);
+        let glyphs = util::Glyphs::new(text, position, metrics.advance, theme.background_color());
+        background.into_iter().chain(glyphs)
+    }
+
+    fn layout_style(&self, theme: &T) -> layout::Style {
+        let metrics = Metrics::new(theme);
+        let width = self.toast.text.len() as i32 * metrics.advance + 2 * metrics.padding + 2;
+        // The most recent toast is the lowest
+        let below = (self.count - 1 - self.index) as i32;
+        let bottom = metrics.padding + below * (metrics.height + metrics.padding);
+        layout::Style {
+            position_type: stretch::style::PositionType::Absolute,
+            position: stretch::geometry::Rect {
+                bottom: stretch::style::Dimension::Points(bottom as f32),
+                ..Default::default()
+            },
+            align_self: stretch::style::AlignSelf::Center,
+            size: stretch::geometry::Size {
+                width: stretch::style::Dimension::Points(width as f32),
+                height: stretch::style::Dimension::Points(metrics.height as f32),
+            },
+            margin: Default::default(),
+            ..theme.base_style()
+        }
+    }
+
+    fn layout_state(&self) -> &layout::State {
+        &*self.layout
+    }
+
+    fn layout_state_mut(&mut self) -> &mut layout::State {
+        &mut *self.layout
+    }
+
+    fn layer(&self) -> u8 {
+        TOAST_LAYER
+    }
+
+    fn animate(&mut self, context: &widget::AnimationContext) {
+        let toast = &mut *self.toast;
+        let expires = *toast.expires.get_or_insert(context.now + toast.duration);
+        if expires <= context.now {
+            toast.dismissed = true;
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        context: &mut widget::EventContext<T, M>,
+        event: &event::Event,
+    ) -> bool {
+        let pointer = match (context.phase, event) {
+            (event::Phase::Target, event::Event::Pointer(pointer)) => pointer,
+            _ => return false,
+        };
+
+        let toast = &mut *self.toast;
+        match pointer.action {
+            event::PointerAction::Down => {
+                toast.pressed = true;
+                true
+            }
+            event::PointerAction::Move => toast.pressed,
+            event::PointerAction::Up => {
+                let pressed = toast.pressed;
+                toast.pressed = false;
+                toast.dismissed |= pressed && context.contains(pointer.position);
+                pressed
+            }
+        }
+    }
+}
+
+impl Metrics {
+    fn new<'a, T>(theme: &T) -> Self
+    where
+        T: theme::Theme<'a>,
+    {
+        use embedded_graphics::fonts::Font;
+        use embedded_graphics::geometry::Dimensions;
+
+        let glyph_size = T::Font::render_str("0").size();
+        let padding = theme.spacing() as i32;
+        Metrics {
+            padding,
+            advance: glyph_size.width as i32,
+            height: glyph_size.height as i32 + 2 * padding,
+        }
+    }
+}
+
+/// Removes the toast at `index`, keeping the others in order.
+fn remove(toasts: &mut heapless::Vec<Toast, MaxToasts>, index: usize) {
+    if index < toasts.len() {
+        toasts[index..].rotate_left(1);
+        toasts.pop();
+    }
+}
+
+/// Removes the toasts that have expired or been tapped.
+fn remove_dismissed(toasts: &mut heapless::Vec<Toast, MaxToasts>) {
+    let mut index = 0;
+    while index < toasts.len() {
+        if toasts[index].dismissed {
+            remove(toasts, index);
+        } else {
+            index += 1;
+        }
+    }
+}
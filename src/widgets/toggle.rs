@@ -0,0 +1,70 @@
+use crate::theme;
+use crate::util;
+use crate::widget;
+use crate::widgets::switch;
+
+pub use switch::State;
+
+/// A labelled switch that is on or off.
+///
+/// Like a checkbox, the state is owned by the application, which should update it in response to
+/// the message emitted when the switch is toggled.
+pub type Toggle<'a, M> = switch::Switch<'a, M, Knob>;
+
+/// The track of a toggle, with a knob that slides to the right when it is on.
+#[derive(Debug)]
+pub struct Knob;
+
+impl<'a, T> switch::Indicator<'a, T> for Knob
+where
+    T: theme::Theme<'a>,
+{
+    type Output = core::iter::Chain<util::RectIter<T::Color>, util::RectIter<T::Color>>;
+
+    fn width() -> i32 {
+        // The knob is a square in either half of the track
+        2
+    }
+
+    fn draw(
+        on: bool,
+        context: &widget::DrawContext<T>,
+        top_left: embedded_graphics::geometry::Point,
+        side: i32,
+    ) -> Self::Output {
+        let theme = context.theme;
+        let (x1, y1) = (top_left.x, top_left.y);
+        let x2 = x1 + 2 * side - 1;
+        let y2 = y1 + side - 1;
+
+        // The knob is drawn in a color that contrasts with the track, also on monochrome displays
+        let (track_fill, knob_x1, knob_color) = if on {
+            (
+                Some(theme.fill_color()),
+                x1 + side + 1,
+                theme.background_color(),
+            )
+        } else if context.hovered {
+            (Some(theme.hover_color()), x1 + 2, theme.border_color())
+        } else {
+            (None, x1 + 2, theme.border_color())
+        };
+
+        let track_iter = embedded_graphics::egrectangle!(
+            (x1, y1),
+            (x2, y2),
+            fill = track_fill,
+            stroke = Some(theme.border_color())
+        )
+        .into_iter();
+
+        let knob_iter = embedded_graphics::egrectangle!(
+            (knob_x1, y1 + 2),
+            (knob_x1 + side - 4, y2 - 2),
+            fill = Some(knob_color)
+        )
+        .into_iter();
+
+        track_iter.chain(knob_iter)
+    }
+}